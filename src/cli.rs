@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::{
+  client::{ClientProfile, RecordingConfig},
   error::Result,
   server::{self, EndpointConfig, ServerConfig},
   util::relative_path,
@@ -61,6 +62,8 @@ struct TestConfig {
   /// The base URL of the feed, used for resolving relative urls
   #[clap(long)]
   base: Option<Url>,
+  #[clap(flatten)]
+  recording: RecordingConfig,
 }
 
 impl TestConfig {
@@ -79,6 +82,9 @@ impl TestConfig {
 )]
 pub struct RootConfig {
   pub auth: Option<AuthConfig>,
+  /// Client settings applied to all requests to matching domains
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub client_profiles: Vec<ClientProfile>,
   pub endpoints: Vec<EndpointConfig>,
 }
 
@@ -96,16 +102,28 @@ impl RootConfig {
     let endpoint = EndpointConfig::default_on_the_fly(path);
     Self {
       auth: None,
+      client_profiles: Vec::new(),
       endpoints: vec![endpoint],
     }
   }
 
+  /// Read the config. Relative paths in it are resolved against the
+  /// directory of the file from now on, which stays the same when the
+  /// config is reloaded.
   pub fn load_from_file(path: &Path) -> Result<Self> {
     let f = std::fs::File::open(path)?;
     let root_config: Self = serde_yaml::from_reader(f)?;
+    crate::util::set_config_dir(path);
     Ok(root_config)
   }
 
+  /// Apply the settings shared by all endpoints. Called once the
+  /// whole config is loaded, so a config that fails to load doesn't
+  /// affect the running one.
+  pub fn install(&self) {
+    crate::client::set_profiles(&self.client_profiles);
+  }
+
   fn get_endpoint(&self, endpoint: &str) -> Option<EndpointConfig> {
     self
       .endpoints
@@ -130,6 +148,8 @@ impl Cli {
           .config
           .expect("config file is required for endpoint testing");
         let feed_defn = RootConfig::load_from_file(&config)?;
+        feed_defn.install();
+        test_config.recording.install();
         test_endpoint(feed_defn, &test_config).await;
         Ok(())
       }
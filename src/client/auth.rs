@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+/// A secret kept out of the config file. Exactly one of the fields
+/// must be set.
+#[serde_with::skip_serializing_none]
+#[derive(
+  JsonSchema, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash,
+)]
+pub struct Secret {
+  /// Read the secret from this environment variable
+  #[serde(default)]
+  pub env: Option<String>,
+  /// Read the secret from this file. A relative path is resolved
+  /// against the directory of the config file. Surrounding whitespace
+  /// is removed.
+  #[serde(default)]
+  pub file: Option<PathBuf>,
+}
+
+impl Secret {
+  pub fn read(&self) -> Result<String> {
+    match (&self.env, &self.file) {
+      (Some(name), None) => std::env::var(name).map_err(|e| {
+        anyhow::anyhow!("failed to read secret from env {name}: {e}")
+      }),
+      (None, Some(path)) => {
+        let path = crate::util::resolve_config_path(path);
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+          anyhow::anyhow!(
+            "failed to read secret from file {}: {e}",
+            path.display()
+          )
+        })?;
+        Ok(content.trim().to_owned())
+      }
+      _ => anyhow::bail!("exactly one of env and file must be set"),
+    }
+  }
+}
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash,
+)]
+pub struct BasicAuth {
+  /// The user name
+  pub username: String,
+  /// The password
+  pub password: Secret,
+}
@@ -1,12 +1,88 @@
 use std::sync::Arc;
 
+use encoding_rs::Encoding;
 use mime::Mime;
-use reqwest::header::HeaderMap;
+use regex::bytes::Regex;
+use reqwest::header::{HeaderMap, HeaderValue};
 use url::Url;
 
-use crate::{Result, error::HttpStatusError, util::TimedLruCache};
+use crate::{
+  Result,
+  error::{HttpStatusError, ResponseTooLarge},
+  util::TimedLruCache,
+};
 
-pub type ResponseCache = TimedLruCache<Url, Response>;
+pub type ResponseCache = TimedLruCache<RequestKey, Response>;
+
+/// What responses are cached by. Requests for the same url can get
+/// different responses depending on the method, body and credentials.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct RequestKey {
+  method: reqwest::Method,
+  url: Url,
+  body: Option<String>,
+  // only a hash, so the credentials aren't kept around in the cache
+  auth: Option<blake3::Hash>,
+}
+
+impl RequestKey {
+  pub fn new(
+    method: &reqwest::Method,
+    url: &Url,
+    body: Option<&str>,
+    auth: Option<&HeaderValue>,
+  ) -> Self {
+    Self {
+      method: method.clone(),
+      url: url.clone(),
+      body: body.map(str::to_owned),
+      auth: auth.map(|value| blake3::hash(value.as_bytes())),
+    }
+  }
+
+  pub fn method(&self) -> &reqwest::Method {
+    &self.method
+  }
+
+  pub fn url(&self) -> &Url {
+    &self.url
+  }
+
+  pub fn body(&self) -> Option<&str> {
+    self.body.as_deref()
+  }
+
+  /// A hash of the key that, unlike `Hash`, stays the same across
+  /// runs.
+  pub fn digest(&self) -> blake3::Hash {
+    let mut hasher = blake3::Hasher::new();
+    // neither the method nor the url can contain a space or newline
+    hasher.update(self.method.as_str().as_bytes());
+    hasher.update(b" ");
+    hasher.update(self.url.as_str().as_bytes());
+    if let Some(body) = &self.body {
+      hasher.update(b"\nbody ");
+      hasher.update(&(body.len() as u64).to_le_bytes());
+      hasher.update(body.as_bytes());
+    }
+    if let Some(auth) = &self.auth {
+      hasher.update(b"\nauth ");
+      hasher.update(auth.as_bytes());
+    }
+    hasher.finalize()
+  }
+}
+
+// how far into the body to look for an encoding declaration
+const CHARSET_SNIFF_LEN: usize = 1024;
+
+lazy_static::lazy_static! {
+  static ref XML_ENCODING_RE: Regex =
+    Regex::new(r#"^\s*<\?xml\s[^>]*?encoding\s*=\s*["']([\w.:-]+)["']"#)
+      .unwrap();
+  static ref HTML_CHARSET_RE: Regex =
+    Regex::new(r#"(?i)<meta\s[^>]*?charset\s*=\s*["']?([\w.:-]+)"#).unwrap();
+}
 
 #[derive(Clone)]
 pub struct Response {
@@ -21,11 +97,32 @@ struct InnerResponse {
 }
 
 impl Response {
-  pub async fn from_reqwest_resp(resp: reqwest::Response) -> Result<Self> {
+  /// Read the response, giving up once the body exceeds `max_size`
+  /// bytes.
+  pub async fn from_reqwest_resp(
+    mut resp: reqwest::Response,
+    max_size: usize,
+  ) -> Result<Self> {
     let status = resp.status();
     let headers = resp.headers().clone();
     let url = resp.url().clone();
-    let body = resp.bytes().await?.to_vec().into_boxed_slice();
+
+    let too_large = || ResponseTooLarge(url.clone(), max_size);
+    if resp
+      .content_length()
+      .is_some_and(|len| len > max_size as u64)
+    {
+      return Err(too_large().into());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+      if body.len() + chunk.len() > max_size {
+        return Err(too_large().into());
+      }
+      body.extend_from_slice(&chunk);
+    }
+    let body = body.into_boxed_slice();
     let resp = InnerResponse {
       url,
       status,
@@ -38,7 +135,6 @@ impl Response {
     })
   }
 
-  #[cfg(test)]
   pub fn new(
     url: Url,
     status: reqwest::StatusCode,
@@ -104,18 +200,46 @@ impl Response {
     self.inner.headers.get(name).and_then(|v| v.to_str().ok())
   }
 
+  pub fn etag(&self) -> Option<&str> {
+    self.header("etag")
+  }
+
+  pub fn last_modified(&self) -> Option<&str> {
+    self.header("last-modified")
+  }
+
+  /// Make the request conditional on the response having changed
+  /// since this one, if the server gave us a way to tell.
+  pub fn revalidate(
+    &self,
+    mut builder: reqwest::RequestBuilder,
+  ) -> reqwest::RequestBuilder {
+    if let Some(etag) = self.etag() {
+      builder = builder.header("If-None-Match", etag);
+    }
+    if let Some(last_modified) = self.last_modified() {
+      builder = builder.header("If-Modified-Since", last_modified);
+    }
+    builder
+  }
+
+  /// Decode the body using, in order of precedence: the byte order
+  /// mark, the charset in the content type, the encoding declared in
+  /// the XML prolog or an HTML meta tag, then `default_encoding`.
   pub fn text_with_charset(&self, default_encoding: &str) -> Result<String> {
-    let content_type = self.content_type();
-    let encoding_name = content_type
-      .as_ref()
-      .and_then(|mime| {
-        mime.get_param("charset").map(|charset| charset.as_str())
+    let body = self.body();
+    let encoding = Encoding::for_bom(body)
+      .map(|(encoding, _bom_len)| encoding)
+      .or_else(|| {
+        let content_type = self.content_type()?;
+        let charset = content_type.get_param("charset")?;
+        Encoding::for_label(charset.as_str().as_bytes())
       })
-      .unwrap_or(default_encoding);
-    let encoding = encoding_rs::Encoding::for_label(encoding_name.as_bytes())
+      .or_else(|| declared_encoding(body))
+      .or_else(|| Encoding::for_label(default_encoding.as_bytes()))
       .unwrap_or(encoding_rs::UTF_8);
 
-    let (text, _, _) = encoding.decode(self.body());
+    let (text, _, _) = encoding.decode(body);
     Ok(text.into_owned())
   }
 
@@ -141,7 +265,6 @@ impl Response {
   pub fn url(&self) -> &Url {
     &self.inner.url
   }
-  #[allow(dead_code)]
   pub fn status(&self) -> reqwest::StatusCode {
     self.inner.status
   }
@@ -154,3 +277,65 @@ impl Response {
     &self.inner.body
   }
 }
+
+// The encoding declared in the document itself. Reading the
+// declaration at all means the document is ASCII compatible, so a
+// declared UTF-16 is taken as UTF-8 (see `output_encoding`).
+fn declared_encoding(body: &[u8]) -> Option<&'static Encoding> {
+  let head = &body[..body.len().min(CHARSET_SNIFF_LEN)];
+  let captures = XML_ENCODING_RE
+    .captures(head)
+    .or_else(|| HTML_CHARSET_RE.captures(head))?;
+  let encoding = Encoding::for_label(captures.get(1)?.as_bytes())?;
+  Some(encoding.output_encoding())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn response(content_type: &str, body: &[u8]) -> Response {
+    let mut headers = HeaderMap::new();
+    headers.insert("content-type", content_type.parse().unwrap());
+    let url = Url::parse("https://example.com/").unwrap();
+    Response::new(url, reqwest::StatusCode::OK, headers, body.into())
+  }
+
+  fn encode(encoding: &'static Encoding, text: &str) -> Vec<u8> {
+    encoding.encode(text).0.into_owned()
+  }
+
+  #[test]
+  fn test_text_from_xml_prolog() {
+    let xml =
+      r#"<?xml version="1.0" encoding="windows-1251"?><title>Привет</title>"#;
+    let body = encode(encoding_rs::WINDOWS_1251, xml);
+    let text = response("text/xml", &body).text().unwrap();
+    assert!(text.contains("Привет"));
+
+    // the content type takes precedence
+    let text = response("text/xml; charset=utf-8", &body).text().unwrap();
+    assert!(!text.contains("Привет"));
+  }
+
+  #[test]
+  fn test_text_from_html_meta() {
+    let html = r#"<html><head><meta charset="Shift_JIS"></head><p>日本語</p>"#;
+    let body = encode(encoding_rs::SHIFT_JIS, html);
+    let text = response("text/html", &body).text().unwrap();
+    assert!(text.contains("日本語"));
+
+    let html = r#"<meta http-equiv="Content-Type" content="text/html; charset=euc-kr"><p>한국어</p>"#;
+    let body = encode(encoding_rs::EUC_KR, html);
+    let text = response("text/html", &body).text().unwrap();
+    assert!(text.contains("한국어"));
+  }
+
+  #[test]
+  fn test_text_from_bom() {
+    let mut body = vec![0xff, 0xfe];
+    body.extend("<p>é</p>".encode_utf16().flat_map(u16::to_le_bytes));
+    let text = response("text/html; charset=iso-8859-1", &body).text();
+    assert_eq!(text.unwrap(), "<p>é</p>");
+  }
+}
@@ -0,0 +1,283 @@
+//! Responses kept on disk so they survive restarts and config reloads.
+//!
+//! Each response is stored in its own file, named after the hash of
+//! its request: a line of JSON metadata followed by the raw body. The
+//! age of an entry is the time it was written, so the same TTL applies
+//! as for the in-memory cache.
+
+use std::{
+  path::{Path, PathBuf},
+  time::{Duration, SystemTime},
+};
+
+use reqwest::{
+  StatusCode,
+  header::{HeaderMap, HeaderName, HeaderValue},
+};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tracing::warn;
+use url::Url;
+
+use super::cache::{RequestKey, Response};
+use crate::error::Result;
+
+pub const DEFAULT_MAX_SIZE: u64 = 100 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize)]
+struct Meta {
+  url: Url,
+  status: u16,
+  headers: Vec<(String, String)>,
+}
+
+// Every client with a `cache_dir` has its own `DiskCache`, and several
+// of them can share a directory, so nothing about the directory is
+// kept in memory.
+pub struct DiskCache {
+  dir: PathBuf,
+  max_size: u64,
+}
+
+impl DiskCache {
+  pub fn new(dir: PathBuf, max_size: u64) -> Self {
+    Self { dir, max_size }
+  }
+
+  /// Get the cached response and how long ago it was stored.
+  pub async fn get(&self, key: &RequestKey) -> Option<(Response, Duration)> {
+    let path = self.path(key);
+    let content = fs::read(&path).await.ok()?;
+    let age = fs::metadata(&path)
+      .await
+      .and_then(|m| m.modified())
+      .ok()
+      .and_then(|t| SystemTime::now().duration_since(t).ok())
+      .unwrap_or_default();
+
+    match decode(&content) {
+      Ok(resp) => Some((resp, age)),
+      Err(e) => {
+        warn!("ignoring corrupted cache entry {}: {e}", path.display());
+        None
+      }
+    }
+  }
+
+  /// Store the response if successful, evicting the oldest entries if
+  /// the cache grows over its size. Failures are only logged, as the
+  /// cache is an optimization.
+  pub async fn insert(&self, key: &RequestKey, resp: &Response) {
+    if !resp.status().is_success() {
+      return;
+    }
+    if let Err(e) = self.insert_impl(key, resp).await {
+      warn!("failed to write cache entry for {}: {e}", key.url());
+    }
+  }
+
+  async fn insert_impl(&self, key: &RequestKey, resp: &Response) -> Result<()> {
+    fs::create_dir_all(&self.dir).await?;
+
+    // write to a temporary file first so readers never see a
+    // partially written entry
+    let path = self.path(key);
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, encode(resp)?).await?;
+    fs::rename(&tmp_path, &path).await?;
+
+    self.evict(&path).await
+  }
+
+  fn path(&self, key: &RequestKey) -> PathBuf {
+    self.dir.join(key.digest().to_hex().as_str())
+  }
+
+  // Remove the oldest entries until the cache fits in its size. `keep`
+  // is the entry just written, which stays even if it alone exceeds
+  // the size.
+  async fn evict(&self, keep: &Path) -> Result<()> {
+    let mut entries = vec![];
+    let mut total_size = 0;
+    let mut dir = fs::read_dir(&self.dir).await?;
+    while let Some(entry) = dir.next_entry().await? {
+      let meta = entry.metadata().await?;
+      total_size += meta.len();
+      if !meta.is_file() || entry.path() == keep {
+        continue;
+      }
+      entries.push((meta.modified()?, meta.len(), entry.path()));
+    }
+
+    // oldest first
+    entries.sort();
+    for (_, size, path) in entries {
+      if total_size <= self.max_size {
+        break;
+      }
+      if let Err(e) = fs::remove_file(&path).await {
+        warn!("failed to evict cache entry {}: {e}", path.display());
+      }
+      total_size -= size;
+    }
+
+    Ok(())
+  }
+}
+
+pub(super) fn encode(resp: &Response) -> Result<Vec<u8>> {
+  let headers = resp
+    .headers()
+    .iter()
+    .filter_map(|(name, value)| {
+      Some((name.to_string(), value.to_str().ok()?.to_owned()))
+    })
+    .collect();
+  let meta = Meta {
+    url: resp.url().clone(),
+    status: resp.status().as_u16(),
+    headers,
+  };
+
+  // serde_json escapes newlines, so the metadata is a single line
+  let mut content = serde_json::to_vec(&meta)?;
+  content.push(b'\n');
+  content.extend_from_slice(resp.body());
+  Ok(content)
+}
+
+pub(super) fn decode(content: &[u8]) -> Result<Response> {
+  let Some(newline) = content.iter().position(|&b| b == b'\n') else {
+    anyhow::bail!("missing metadata");
+  };
+  let meta: Meta = serde_json::from_slice(&content[..newline])?;
+
+  let mut headers = HeaderMap::new();
+  for (name, value) in meta.headers {
+    headers.append(HeaderName::try_from(name)?, HeaderValue::try_from(value)?);
+  }
+
+  Ok(Response::new(
+    meta.url,
+    StatusCode::from_u16(meta.status)?,
+    headers,
+    content[newline + 1..].into(),
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn key(url: &Url) -> RequestKey {
+    RequestKey::new(&reqwest::Method::GET, url, None, None)
+  }
+
+  fn response(url: &Url, body: &str) -> Response {
+    let mut headers = HeaderMap::new();
+    headers.insert("etag", HeaderValue::from_static("\"v1\""));
+    Response::new(url.clone(), StatusCode::OK, headers, body.as_bytes().into())
+  }
+
+  #[tokio::test]
+  async fn test_disk_cache_skips_errors() {
+    let dir = std::env::temp_dir().join(format!(
+      "rss-funnel-disk-cache-error-test-{}",
+      std::process::id()
+    ));
+    let cache = DiskCache::new(dir.clone(), DEFAULT_MAX_SIZE);
+
+    let url = Url::parse("https://example.com/missing").unwrap();
+    let resp = Response::new(
+      url.clone(),
+      StatusCode::NOT_FOUND,
+      HeaderMap::new(),
+      b"not found".as_slice().into(),
+    );
+    cache.insert(&key(&url), &resp).await;
+    let cached = cache.get(&key(&url)).await.is_some();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert!(!cached);
+  }
+
+  #[tokio::test]
+  async fn test_disk_cache() {
+    let dir = std::env::temp_dir()
+      .join(format!("rss-funnel-disk-cache-test-{}", std::process::id()));
+    let cache = DiskCache::new(dir.clone(), 20);
+
+    let first = Url::parse("https://example.com/first").unwrap();
+    let second = Url::parse("https://example.com/second").unwrap();
+    cache
+      .insert(&key(&first), &response(&first, "0123456789"))
+      .await;
+
+    let (resp, age) = cache.get(&key(&first)).await.unwrap();
+    assert_eq!(resp.body(), b"0123456789");
+    assert_eq!(resp.etag(), Some("\"v1\""));
+    assert!(age < Duration::from_secs(60));
+
+    // the metadata alone exceeds the size, so only the newest entry
+    // is kept
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    cache.insert(&key(&second), &response(&second, "abc")).await;
+    let first_cached = cache.get(&key(&first)).await.is_some();
+    let second_cached = cache.get(&key(&second)).await.is_some();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(!first_cached);
+    assert!(second_cached);
+  }
+
+  #[tokio::test]
+  async fn test_disk_cache_request_key() {
+    let dir = std::env::temp_dir().join(format!(
+      "rss-funnel-disk-cache-key-test-{}",
+      std::process::id()
+    ));
+    let cache = DiskCache::new(dir.clone(), DEFAULT_MAX_SIZE);
+
+    let url = Url::parse("https://example.com/api").unwrap();
+    cache.insert(&key(&url), &response(&url, "get")).await;
+
+    let post = RequestKey::new(&reqwest::Method::POST, &url, Some("{}"), None);
+    let token = HeaderValue::from_static("Bearer s3cret");
+    let with_auth =
+      RequestKey::new(&reqwest::Method::GET, &url, None, Some(&token));
+    let post_cached = cache.get(&post).await.is_some();
+    let auth_cached = cache.get(&with_auth).await.is_some();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(!post_cached);
+    assert!(!auth_cached);
+  }
+
+  #[tokio::test]
+  async fn test_disk_cache_shared_dir() {
+    let dir = std::env::temp_dir().join(format!(
+      "rss-funnel-disk-cache-shared-test-{}",
+      std::process::id()
+    ));
+    let urls: Vec<_> = (1..=3)
+      .map(|i| Url::parse(&format!("https://example.com/{i}")).unwrap())
+      .collect();
+    // room for exactly two entries
+    let entry_size = encode(&response(&urls[0], "abc")).unwrap().len();
+    let first = DiskCache::new(dir.clone(), 2 * entry_size as u64);
+    let second = DiskCache::new(dir.clone(), 2 * entry_size as u64);
+
+    for (cache, url) in [&first, &second, &first].into_iter().zip(&urls) {
+      cache.insert(&key(url), &response(url, "abc")).await;
+      tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let mut cached = vec![];
+    for url in &urls {
+      cached.push(first.get(&key(url)).await.is_some());
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // the entry written by the other cache counts towards the size
+    assert_eq!(cached, [false, true, true]);
+  }
+}
@@ -0,0 +1,172 @@
+//! Limits on the requests made to the same host.
+//!
+//! Every filter and endpoint builds its own `Client`, so the state
+//! lives in a process-wide registry instead. Clients configured with
+//! the same limits share the state for each host. The state of idle
+//! hosts is dropped whenever a new host is seen, so the registry
+//! doesn't keep every host ever requested.
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, LazyLock, Mutex},
+  time::Duration,
+};
+
+use tokio::{
+  sync::{OwnedSemaphorePermit, Semaphore},
+  time::Instant,
+};
+use url::Url;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct HostLimit {
+  pub concurrency: Option<usize>,
+  pub requests_per_second: Option<u32>,
+}
+
+struct HostState {
+  permits: Option<Arc<Semaphore>>,
+  next_request: tokio::sync::Mutex<Instant>,
+}
+
+impl HostState {
+  // Nobody is waiting for or holding a permit, and the next request
+  // may go out right away, so dropping the state loses nothing.
+  fn is_idle(self: &Arc<Self>) -> bool {
+    Arc::strong_count(self) == 1
+      && self
+        .permits
+        .as_ref()
+        .is_none_or(|permits| Arc::strong_count(permits) == 1)
+      && self
+        .next_request
+        .try_lock()
+        .is_ok_and(|next_request| *next_request <= Instant::now())
+  }
+}
+
+type Registry = Mutex<HashMap<(String, HostLimit), Arc<HostState>>>;
+
+static HOSTS: LazyLock<Registry> = LazyLock::new(Default::default);
+
+impl HostLimit {
+  fn is_unlimited(&self) -> bool {
+    self.concurrency.is_none() && self.requests_per_second.is_none()
+  }
+
+  /// Wait for our turn to make a request to the url's host. The
+  /// returned permit must be held until the request is done.
+  pub async fn acquire(&self, url: &Url) -> Option<OwnedSemaphorePermit> {
+    if self.is_unlimited() {
+      return None;
+    }
+
+    let key = (url.host_str().unwrap_or_default().to_owned(), *self);
+    let mut hosts = HOSTS.lock().expect("host registry poisoned");
+    if !hosts.contains_key(&key) {
+      hosts.retain(|_, state| !state.is_idle());
+    }
+    let state = hosts
+      .entry(key)
+      .or_insert_with(|| {
+        Arc::new(HostState {
+          permits: self.concurrency.map(|n| Arc::new(Semaphore::new(n))),
+          next_request: tokio::sync::Mutex::new(Instant::now()),
+        })
+      })
+      .clone();
+    drop(hosts);
+
+    let permit = match &state.permits {
+      Some(permits) => {
+        Some(permits.clone().acquire_owned().await.expect("never closed"))
+      }
+      None => None,
+    };
+
+    if let Some(rps) = self.requests_per_second.filter(|&n| n > 0) {
+      let interval = Duration::from_secs(1) / rps;
+      let mut next_request = state.next_request.lock().await;
+      let now = Instant::now();
+      let slot = (*next_request).max(now);
+      *next_request = slot + interval;
+      drop(next_request);
+      tokio::time::sleep_until(slot).await;
+    }
+
+    permit
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_concurrency_limit() {
+    let limit = HostLimit {
+      concurrency: Some(1),
+      requests_per_second: None,
+    };
+    let url = Url::parse("https://concurrency.example.com/").unwrap();
+    let other = Url::parse("https://other.example.com/").unwrap();
+
+    let permit = limit.acquire(&url).await;
+    let wait = Duration::from_millis(50);
+    assert!(
+      tokio::time::timeout(wait, limit.acquire(&url))
+        .await
+        .is_err()
+    );
+    assert!(
+      tokio::time::timeout(wait, limit.acquire(&other))
+        .await
+        .is_ok()
+    );
+
+    drop(permit);
+    assert!(
+      tokio::time::timeout(wait, limit.acquire(&url))
+        .await
+        .is_ok()
+    );
+  }
+
+  #[tokio::test]
+  async fn test_prune_idle_hosts() {
+    let limit = HostLimit {
+      concurrency: Some(3),
+      requests_per_second: None,
+    };
+    let busy = Url::parse("https://busy.prune.example.com/").unwrap();
+    let idle = Url::parse("https://idle.prune.example.com/").unwrap();
+    let other = Url::parse("https://other.prune.example.com/").unwrap();
+    let registered = |url: &Url| {
+      let key = (url.host_str().unwrap().to_owned(), limit);
+      HOSTS.lock().unwrap().contains_key(&key)
+    };
+
+    let _permit = limit.acquire(&busy).await;
+    drop(limit.acquire(&idle).await);
+    limit.acquire(&other).await;
+
+    assert!(registered(&busy));
+    assert!(!registered(&idle));
+  }
+
+  #[tokio::test]
+  async fn test_rate_limit() {
+    let limit = HostLimit {
+      concurrency: None,
+      requests_per_second: Some(50),
+    };
+    let url = Url::parse("https://rate.example.com/").unwrap();
+
+    let start = Instant::now();
+    for _ in 0..4 {
+      limit.acquire(&url).await;
+    }
+    // the first request goes out right away, then one every 20ms
+    assert!(start.elapsed() >= Duration::from_millis(60));
+  }
+}
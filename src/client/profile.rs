@@ -0,0 +1,234 @@
+//! Client settings shared by all requests to matching domains.
+//!
+//! The profiles come from the root config, and are kept in a
+//! process-wide registry like the config directory. They are only
+//! replaced once a new config is successfully loaded, and clients look
+//! them up on each request, so a client built while loading a config
+//! uses its profiles as soon as it takes effect.
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex, RwLock},
+  time::Duration,
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{Client, ClientConfig};
+use crate::error::Result;
+
+type Registry = RwLock<Vec<Arc<ClientProfile>>>;
+
+#[cfg(not(test))]
+static PROFILES: Registry = RwLock::new(Vec::new());
+
+// Each test runs on its own thread, so the profiles a test sets don't
+// leak into the others running in parallel.
+#[cfg(test)]
+thread_local! {
+  static PROFILES: Registry = const { RwLock::new(Vec::new()) };
+}
+
+#[cfg(not(test))]
+fn registry<R>(f: impl FnOnce(&Registry) -> R) -> R {
+  f(&PROFILES)
+}
+
+#[cfg(test)]
+fn registry<R>(f: impl FnOnce(&Registry) -> R) -> R {
+  PROFILES.with(f)
+}
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+pub struct ClientProfile {
+  /// Glob patterns of the domains the profile applies to,
+  /// e.g. "*.example.com". The first matching profile is used.
+  pub domains: Vec<String>,
+  /// The client settings. Settings specified on a filter or source
+  /// take precedence over these.
+  pub client: ClientConfig,
+}
+
+impl ClientProfile {
+  pub fn matches(&self, url: &Url) -> bool {
+    url
+      .domain()
+      .is_some_and(|domain| crate::util::domain_matches(&self.domains, domain))
+  }
+}
+
+/// Replace the profiles used by all clients.
+pub fn set_profiles(profiles: &[ClientProfile]) {
+  registry(|registry| {
+    if let Ok(mut current) = registry.write() {
+      *current = profiles.iter().cloned().map(Arc::new).collect();
+    }
+  });
+}
+
+pub fn profiles() -> Vec<Arc<ClientProfile>> {
+  registry(|registry| registry.read().map(|p| p.clone()).unwrap_or_default())
+}
+
+/// The clients for the requests of a client matching a profile, built
+/// on first use from the client's config and the profile.
+pub struct ProfileClients {
+  config: ClientConfig,
+  default_cache_ttl: Duration,
+  clients: Mutex<HashMap<Arc<ClientProfile>, Arc<Client>>>,
+}
+
+impl ProfileClients {
+  pub fn new(config: ClientConfig, default_cache_ttl: Duration) -> Self {
+    Self {
+      config,
+      default_cache_ttl,
+      clients: Mutex::new(HashMap::new()),
+    }
+  }
+
+  /// The client for the first profile matching the url, if any.
+  pub fn get(&self, url: &Url) -> Result<Option<Arc<Client>>> {
+    let Some(profile) = profiles().into_iter().find(|p| p.matches(url)) else {
+      return Ok(None);
+    };
+
+    let mut clients = self.clients.lock().expect("poisoned lock");
+    if let Some(client) = clients.get(&profile) {
+      return Ok(Some(client.clone()));
+    }
+
+    let config = self.config.with_defaults(&profile.client);
+    let client =
+      Arc::new(config.build_without_profiles(self.default_cache_ttl)?);
+    clients.insert(profile, client.clone());
+    Ok(Some(client))
+  }
+}
+
+impl ClientConfig {
+  /// Fill the settings left unspecified with the ones from `defaults`,
+  /// e.g. a profile. Headers are merged.
+  pub fn with_defaults(&self, defaults: &ClientConfig) -> ClientConfig {
+    let ClientConfig {
+      user_agent,
+      accept,
+      accept_language,
+      set_cookie,
+      cookie,
+      referer,
+      mut headers,
+      basic_auth,
+      bearer_token,
+      method,
+      body,
+      accept_invalid_certs,
+      cache_size,
+      cache_ttl,
+      cache_dir,
+      cache_dir_size,
+      timeout,
+      assume_content_type,
+      max_body_size,
+      proxy,
+      autodiscover,
+      retries,
+      retry_backoff,
+      retry_statuses,
+      host_concurrency,
+      host_requests_per_second,
+      respect_robots_txt,
+    } = defaults.clone();
+    let this = self.clone();
+    headers.extend(this.headers);
+
+    ClientConfig {
+      user_agent: this.user_agent.or(user_agent),
+      accept: this.accept.or(accept),
+      accept_language: this.accept_language.or(accept_language),
+      set_cookie: this.set_cookie.or(set_cookie),
+      cookie: this.cookie.or(cookie),
+      referer: this.referer.or(referer),
+      headers,
+      basic_auth: this.basic_auth.or(basic_auth),
+      bearer_token: this.bearer_token.or(bearer_token),
+      method: this.method.or(method),
+      body: this.body.or(body),
+      accept_invalid_certs: this.accept_invalid_certs.or(accept_invalid_certs),
+      cache_size: this.cache_size.or(cache_size),
+      cache_ttl: this.cache_ttl.or(cache_ttl),
+      cache_dir: this.cache_dir.or(cache_dir),
+      cache_dir_size: this.cache_dir_size.or(cache_dir_size),
+      timeout: this.timeout.or(timeout),
+      assume_content_type: this.assume_content_type.or(assume_content_type),
+      max_body_size: this.max_body_size.or(max_body_size),
+      proxy: this.proxy.or(proxy),
+      autodiscover: this.autodiscover.or(autodiscover),
+      retries: this.retries.or(retries),
+      retry_backoff: this.retry_backoff.or(retry_backoff),
+      retry_statuses: this.retry_statuses.or(retry_statuses),
+      host_concurrency: this.host_concurrency.or(host_concurrency),
+      host_requests_per_second: this
+        .host_requests_per_second
+        .or(host_requests_per_second),
+      respect_robots_txt: this.respect_robots_txt.or(respect_robots_txt),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_with_defaults() {
+    let profile: ClientProfile = serde_yaml::from_str(
+      r#"
+domains: ["*.example.com"]
+client:
+  user_agent: profile-agent
+  cookie: session=1
+  headers:
+    x-a: profile
+    x-b: profile
+"#,
+    )
+    .unwrap();
+    let config: ClientConfig = serde_yaml::from_str(
+      r#"
+user_agent: own-agent
+headers:
+  x-b: own
+"#,
+    )
+    .unwrap();
+
+    let merged = config.with_defaults(&profile.client);
+    assert_eq!(merged.user_agent.as_deref(), Some("own-agent"));
+    assert_eq!(merged.cookie.as_deref(), Some("session=1"));
+    assert_eq!(merged.headers["x-a"], "profile");
+    assert_eq!(merged.headers["x-b"], "own");
+
+    let profile = ClientConfig {
+      autodiscover: Some(true),
+      respect_robots_txt: Some(true),
+      ..Default::default()
+    };
+    let config = ClientConfig {
+      respect_robots_txt: Some(false),
+      ..Default::default()
+    };
+    let merged = config.with_defaults(&profile);
+    assert_eq!(merged.autodiscover, Some(true));
+    assert_eq!(merged.respect_robots_txt, Some(false));
+
+    let url = |s: &str| Url::parse(s).unwrap();
+    assert!(profile.matches(&url("https://blog.example.com/feed")));
+    assert!(!profile.matches(&url("https://example.com/feed")));
+    assert!(!profile.matches(&url("https://127.0.0.1/feed")));
+  }
+}
@@ -0,0 +1,228 @@
+//! Record the responses of all outgoing requests to a directory, and
+//! replay them later without network access.
+//!
+//! Every endpoint and filter builds its own `Client`, so the mode is
+//! set once for the whole process. Each recorded response is a file
+//! in the same format as the disk cache, named after the host and the
+//! hash of the request.
+
+use std::{
+  fs,
+  future::Future,
+  path::PathBuf,
+  sync::{Arc, RwLock},
+};
+
+use clap::Parser;
+use reqwest::Method;
+use tracing::{info, warn};
+use url::Url;
+
+use super::{
+  cache::Response,
+  disk_cache::{decode, encode},
+};
+use crate::error::Result;
+
+static RECORDING: RwLock<Option<Arc<Recording>>> = RwLock::new(None);
+
+#[derive(Parser, Clone, Debug, Default)]
+pub struct RecordingConfig {
+  /// Record the responses of all outgoing requests to this directory
+  #[clap(
+    long,
+    value_name = "DIR",
+    conflicts_with = "replay",
+    env = "RSS_FUNNEL_RECORD"
+  )]
+  record: Option<PathBuf>,
+
+  /// Serve all outgoing requests from the responses recorded in this
+  /// directory, without network access
+  #[clap(long, value_name = "DIR", env = "RSS_FUNNEL_REPLAY")]
+  replay: Option<PathBuf>,
+}
+
+impl RecordingConfig {
+  /// Set the recording mode for all clients in the process.
+  pub fn install(&self) {
+    let recording = match (&self.record, &self.replay) {
+      (Some(dir), _) => {
+        info!("recording responses to {}", dir.display());
+        Recording::new(dir.clone(), Mode::Record)
+      }
+      (None, Some(dir)) => {
+        info!("replaying responses from {}", dir.display());
+        Recording::new(dir.clone(), Mode::Replay)
+      }
+      (None, None) => return,
+    };
+
+    if let Ok(mut current) = RECORDING.write() {
+      *current = Some(Arc::new(recording));
+    }
+  }
+}
+
+/// Replay the response to the request when replaying. Otherwise get
+/// it with `send`, and record it when recording.
+pub async fn recorded(
+  method: &Method,
+  url: &Url,
+  body: Option<&str>,
+  send: impl Future<Output = Result<Response>>,
+) -> Result<Response> {
+  let recording = Recording::current();
+  if let Some(recording) = &recording
+    && recording.mode() == Mode::Replay
+  {
+    return recording.replay(method, url, body);
+  }
+
+  let resp = send.await?;
+  if let Some(recording) = &recording {
+    recording.record(method, url, body, &resp);
+  }
+  Ok(resp)
+}
+
+/// Same as `recorded`, for the places that can't be async.
+pub fn recorded_blocking(
+  method: &Method,
+  url: &Url,
+  body: Option<&str>,
+  send: impl FnOnce() -> Result<Response>,
+) -> Result<Response> {
+  let recording = Recording::current();
+  if let Some(recording) = &recording
+    && recording.mode() == Mode::Replay
+  {
+    return recording.replay(method, url, body);
+  }
+
+  let resp = send()?;
+  if let Some(recording) = &recording {
+    recording.record(method, url, body, &resp);
+  }
+  Ok(resp)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+  Record,
+  Replay,
+}
+
+pub struct Recording {
+  dir: PathBuf,
+  mode: Mode,
+}
+
+impl Recording {
+  pub fn new(dir: PathBuf, mode: Mode) -> Self {
+    Self { dir, mode }
+  }
+
+  pub fn current() -> Option<Arc<Self>> {
+    RECORDING.read().ok()?.clone()
+  }
+
+  pub fn mode(&self) -> Mode {
+    self.mode
+  }
+
+  pub fn replay(
+    &self,
+    method: &Method,
+    url: &Url,
+    body: Option<&str>,
+  ) -> Result<Response> {
+    let path = self.path(method, url, body);
+    let content = fs::read(&path).map_err(|e| {
+      anyhow::anyhow!(
+        "no recorded response for {method} {url} ({}: {e})",
+        path.display()
+      )
+    })?;
+    decode(&content)
+  }
+
+  /// Save the response. Failures are only logged so they don't break
+  /// the request being recorded.
+  pub fn record(
+    &self,
+    method: &Method,
+    url: &Url,
+    body: Option<&str>,
+    resp: &Response,
+  ) {
+    let path = self.path(method, url, body);
+    let result = fs::create_dir_all(&self.dir)
+      .map_err(Into::into)
+      .and_then(|_| encode(resp))
+      .and_then(|content| Ok(fs::write(&path, content)?));
+
+    if let Err(e) = result {
+      warn!("failed to record response for {method} {url}: {e}");
+    }
+  }
+
+  fn path(&self, method: &Method, url: &Url, body: Option<&str>) -> PathBuf {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(method.as_str().as_bytes());
+    hasher.update(b" ");
+    hasher.update(url.as_str().as_bytes());
+    if let Some(body) = body {
+      hasher.update(b"\n");
+      hasher.update(body.as_bytes());
+    }
+
+    let hash = hasher.finalize().to_hex();
+    let host = url.host_str().unwrap_or("local");
+    self.dir.join(format!("{host}-{}", &hash[..16]))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use reqwest::{
+    StatusCode,
+    header::{HeaderMap, HeaderValue},
+  };
+
+  use super::*;
+
+  #[test]
+  fn test_record_and_replay() {
+    let dir = std::env::temp_dir()
+      .join(format!("rss-funnel-recording-test-{}", std::process::id()));
+    let url = Url::parse("https://example.com/feed.xml").unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert("content-type", HeaderValue::from_static("text/xml"));
+    let resp = Response::new(
+      url.clone(),
+      StatusCode::OK,
+      headers,
+      b"<rss></rss>".as_slice().into(),
+    );
+
+    Recording::new(dir.clone(), Mode::Record).record(
+      &Method::GET,
+      &url,
+      None,
+      &resp,
+    );
+
+    let replay = Recording::new(dir.clone(), Mode::Replay);
+    let replayed = replay.replay(&Method::GET, &url, None);
+    let other_method = replay.replay(&Method::POST, &url, None);
+    let other_body = replay.replay(&Method::GET, &url, Some("{}"));
+    fs::remove_dir_all(&dir).unwrap();
+
+    let replayed = replayed.unwrap();
+    assert_eq!(replayed.body(), b"<rss></rss>");
+    assert_eq!(replayed.header("content-type"), Some("text/xml"));
+    assert!(other_method.is_err());
+    assert!(other_body.is_err());
+  }
+}
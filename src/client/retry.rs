@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use chrono::DateTime;
+use reqwest::StatusCode;
+
+pub const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+pub const DEFAULT_STATUSES: [u16; 5] = [429, 500, 502, 503, 504];
+// don't let a server park a request for too long
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+pub struct RetryPolicy {
+  pub retries: u32,
+  pub backoff: Duration,
+  pub statuses: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    Self {
+      retries: 0,
+      backoff: DEFAULT_BACKOFF,
+      statuses: DEFAULT_STATUSES.to_vec(),
+    }
+  }
+}
+
+impl RetryPolicy {
+  /// How long to wait before retrying the failed attempt, or None if
+  /// it shouldn't be retried. `attempt` starts from 0.
+  pub fn delay(
+    &self,
+    attempt: u32,
+    result: &reqwest::Result<reqwest::Response>,
+  ) -> Option<Duration> {
+    if attempt >= self.retries {
+      return None;
+    }
+
+    let retry_after = match result {
+      Ok(resp) if self.statuses.contains(&resp.status().as_u16()) => {
+        retry_after(resp)
+      }
+      Err(e) if e.is_timeout() || e.is_connect() => None,
+      _ => return None,
+    };
+
+    let backoff = self.backoff.saturating_mul(2u32.saturating_pow(attempt));
+    Some(retry_after.map_or(backoff, |d| d.min(MAX_RETRY_AFTER)))
+  }
+}
+
+// Retry-After is either a number of seconds or an HTTP date
+fn retry_after(resp: &reqwest::Response) -> Option<Duration> {
+  if !matches!(
+    resp.status(),
+    StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+  ) {
+    return None;
+  }
+
+  let value = resp.headers().get("retry-after")?.to_str().ok()?.trim();
+  if let Ok(secs) = value.parse::<u64>() {
+    return Some(Duration::from_secs(secs));
+  }
+
+  let date = DateTime::parse_from_rfc2822(value).ok()?;
+  let delay = date.signed_duration_since(chrono::Utc::now());
+  Some(delay.to_std().unwrap_or_default())
+}
@@ -0,0 +1,269 @@
+//! A minimal [robots.txt](https://www.rfc-editor.org/rfc/rfc9309)
+//! implementation: groups selected by user agent, `allow`/`disallow`
+//! rules with `*` and `$` wildcards, and the longest match winning.
+//!
+//! A robots.txt that doesn't exist (4xx) allows everything. One that
+//! is unreachable (5xx or a network error) means the last copy we got
+//! still applies, as RFC 9309 allows, or a complete disallow if there
+//! is none. Either way it is fetched again after a few minutes rather
+//! than on every request.
+
+use std::{sync::Arc, time::Duration};
+
+use url::Url;
+
+use super::Client;
+use crate::{
+  error::{DisallowedByRobots, Result},
+  util::TimedLruCache,
+};
+
+// RFC 9309 asks crawlers not to use a cached robots.txt for more than
+// 24 hours
+const ROBOTS_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const UNREACHABLE_TTL: Duration = Duration::from_secs(10 * 60);
+// and allows ignoring anything after the first 500KiB
+const MAX_ROBOTS_SIZE: usize = 500 * 1024;
+const CACHE_SIZE: usize = 64;
+
+#[derive(Debug, Default)]
+pub struct RobotsTxt {
+  groups: Vec<Group>,
+}
+
+#[derive(Debug, Default)]
+struct Group {
+  agents: Vec<String>,
+  rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+  allow: bool,
+  pattern: String,
+}
+
+impl RobotsTxt {
+  pub fn parse(text: &str) -> Self {
+    let mut groups: Vec<Group> = vec![];
+    let mut in_rules = false;
+
+    for line in text.lines() {
+      let line = line.split('#').next().unwrap_or_default();
+      let Some((key, value)) = line.split_once(':') else {
+        continue;
+      };
+      let value = value.trim();
+
+      match key.trim().to_ascii_lowercase().as_str() {
+        "user-agent" => {
+          // consecutive user-agent lines share the same group
+          if in_rules || groups.is_empty() {
+            groups.push(Group::default());
+            in_rules = false;
+          }
+          let group = groups.last_mut().expect("group just pushed");
+          group.agents.push(value.to_ascii_lowercase());
+        }
+        key @ ("allow" | "disallow") => {
+          in_rules = true;
+          // an empty disallow rule allows everything
+          let Some(group) = groups.last_mut() else {
+            continue;
+          };
+          if !value.is_empty() {
+            group.rules.push(Rule {
+              allow: key == "allow",
+              pattern: value.to_owned(),
+            });
+          }
+        }
+        // sitemap, crawl-delay, etc.
+        _ => {}
+      }
+    }
+
+    Self { groups }
+  }
+
+  fn disallow_all() -> Self {
+    Self::parse("user-agent: *\ndisallow: /")
+  }
+
+  /// `agent` is the product token of the user agent, e.g. "rss-funnel"
+  pub fn is_allowed(&self, agent: &str, url: &Url) -> bool {
+    let agent = agent.to_ascii_lowercase();
+    let mut path = url.path().to_owned();
+    if let Some(query) = url.query() {
+      path.push('?');
+      path.push_str(query);
+    }
+
+    let matching = |pred: &dyn Fn(&str) -> bool| {
+      self
+        .groups
+        .iter()
+        .filter(|g| g.agents.iter().any(|a| pred(a)))
+        .flat_map(|g| &g.rules)
+        .collect::<Vec<_>>()
+    };
+    let mut rules = matching(&|a| a != "*" && agent.starts_with(a));
+    if rules.is_empty() {
+      rules = matching(&|a| a == "*");
+    }
+
+    // the longest match wins, allow wins a tie
+    rules
+      .into_iter()
+      .filter(|rule| pattern_matches(&rule.pattern, &path))
+      .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+      .is_none_or(|rule| rule.allow)
+  }
+}
+
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+  let (pattern, anchored) = match pattern.strip_suffix('$') {
+    Some(pattern) => (pattern, true),
+    None => (pattern, false),
+  };
+
+  let mut parts = pattern.split('*');
+  let first = parts.next().unwrap_or_default();
+  let Some(mut rest) = path.strip_prefix(first) else {
+    return false;
+  };
+
+  let parts: Vec<_> = parts.collect();
+  for (i, part) in parts.iter().enumerate() {
+    if anchored && i == parts.len() - 1 {
+      return rest.ends_with(part);
+    }
+    match rest.find(part) {
+      Some(pos) => rest = &rest[pos + part.len()..],
+      None => return false,
+    }
+  }
+
+  !anchored || rest.is_empty()
+}
+
+/// The robots.txt of each host, fetched on first use.
+pub struct RobotsCache {
+  agent: String,
+  cache: TimedLruCache<String, Arc<RobotsTxt>>,
+}
+
+impl RobotsCache {
+  pub fn new(user_agent: &str) -> Self {
+    // the product token, e.g. "rss-funnel" for "rss-funnel/0.1.4"
+    let agent = user_agent
+      .split(['/', ' '])
+      .next()
+      .unwrap_or_default()
+      .to_owned();
+
+    Self {
+      agent,
+      cache: TimedLruCache::new(CACHE_SIZE, ROBOTS_TTL),
+    }
+  }
+
+  pub async fn check(&self, client: &Client, url: &Url) -> Result<()> {
+    if url.path() == "/robots.txt" {
+      return Ok(());
+    }
+
+    let origin = url.origin().ascii_serialization();
+    let robots = match self.cache.get_cached(&origin) {
+      Some(robots) => robots,
+      None => match fetch(client, &origin).await {
+        Some(robots) => {
+          let robots = Arc::new(robots);
+          self.cache.insert(origin, robots.clone());
+          robots
+        }
+        None => {
+          let robots = self
+            .cache
+            .get_stale(&origin)
+            .unwrap_or_else(|| Arc::new(RobotsTxt::disallow_all()));
+          // expires after UNREACHABLE_TTL
+          let age = ROBOTS_TTL.saturating_sub(UNREACHABLE_TTL);
+          self.cache.insert_with_age(origin, robots.clone(), age);
+          robots
+        }
+      },
+    };
+
+    if !robots.is_allowed(&self.agent, url) {
+      return Err(DisallowedByRobots(url.clone()).into());
+    }
+
+    Ok(())
+  }
+}
+
+// None if the robots.txt is unreachable. Fetched with the client
+// itself, so retries, host limits and recording apply.
+async fn fetch(client: &Client, origin: &str) -> Option<RobotsTxt> {
+  let url = Url::parse(&format!("{origin}/robots.txt")).ok()?;
+  let resp = match Box::pin(client.get(&url)).await {
+    Ok(resp) => resp,
+    Err(e) => {
+      tracing::warn!("failed to fetch {url}: {e}");
+      return None;
+    }
+  };
+
+  let status = resp.status();
+  if status.is_success() {
+    let body = resp.body();
+    let body = &body[..body.len().min(MAX_ROBOTS_SIZE)];
+    Some(RobotsTxt::parse(&String::from_utf8_lossy(body)))
+  } else if status.is_client_error() {
+    // no robots.txt means no restrictions
+    Some(RobotsTxt::default())
+  } else {
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const ROBOTS: &str = "
+# comment
+User-agent: rss-funnel
+User-agent: other-bot
+Disallow: /private
+Allow: /private/public
+Disallow: /*.pdf$
+
+User-agent: *
+Disallow: /
+";
+
+  fn allowed(robots: &RobotsTxt, agent: &str, path: &str) -> bool {
+    let url = Url::parse("https://example.com")
+      .unwrap()
+      .join(path)
+      .unwrap();
+    robots.is_allowed(agent, &url)
+  }
+
+  #[test]
+  fn test_robots_txt() {
+    let robots = RobotsTxt::parse(ROBOTS);
+
+    assert!(allowed(&robots, "rss-funnel", "/"));
+    assert!(!allowed(&robots, "rss-funnel", "/private/page"));
+    assert!(allowed(&robots, "rss-funnel", "/private/public/page"));
+    assert!(!allowed(&robots, "rss-funnel", "/files/a.pdf"));
+    assert!(allowed(&robots, "rss-funnel", "/files/a.pdf?download"));
+    assert!(allowed(&robots, "Other-Bot", "/"));
+
+    assert!(!allowed(&robots, "unknown", "/"));
+    assert!(allowed(&RobotsTxt::default(), "unknown", "/"));
+  }
+}
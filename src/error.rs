@@ -59,6 +59,34 @@ pub struct MissingSourceTemplatePlaceholder(pub String);
 #[error("source URL unspecified for dynamic source")]
 pub struct DynamicSourceUnspecified;
 
+#[derive(Debug, thiserror::Error)]
+#[error("command {0} timed out after {1:?}")]
+pub struct CommandTimeout(pub String, pub std::time::Duration);
+
+#[derive(Debug, thiserror::Error)]
+#[error("output of command {0} exceeds {1} bytes")]
+pub struct CommandOutputTooLarge(pub String, pub usize);
+
+#[derive(Debug, thiserror::Error)]
+#[error("response from {0} exceeds {1} bytes")]
+pub struct ResponseTooLarge(pub url::Url, pub usize);
+
+#[derive(Debug, thiserror::Error)]
+#[error("{0} is disallowed by robots.txt")]
+pub struct DisallowedByRobots(pub url::Url);
+
+#[derive(Debug, thiserror::Error)]
+#[error("all mirrors failed: {}", format_failures(.0))]
+pub struct AllMirrorsFailed(pub Vec<(String, String)>);
+
+fn format_failures(failures: &[(String, String)]) -> String {
+  failures
+    .iter()
+    .map(|(source, error)| format!("{source} ({error})"))
+    .collect::<Vec<_>>()
+    .join("; ")
+}
+
 #[derive(Debug, thiserror::Error)]
 #[error(
   "Can't infer app base, please refer to https://github.com/shouya/rss-funnel/wiki/App-base"
@@ -78,6 +106,21 @@ pub fn into_http(e: anyhow::Error) -> (StatusCode, String) {
     {
       return (StatusCode::BAD_REQUEST, format!("{e:?}"));
     }
+
+    if cause.downcast_ref::<DisallowedByRobots>().is_some() {
+      return (StatusCode::FORBIDDEN, format!("{e:?}"));
+    }
+
+    if cause.downcast_ref::<CommandTimeout>().is_some() {
+      return (StatusCode::GATEWAY_TIMEOUT, format!("{e:?}"));
+    }
+
+    if cause.downcast_ref::<CommandOutputTooLarge>().is_some()
+      || cause.downcast_ref::<ResponseTooLarge>().is_some()
+      || cause.downcast_ref::<AllMirrorsFailed>().is_some()
+    {
+      return (StatusCode::BAD_GATEWAY, format!("{e:?}"));
+    }
   }
 
   (StatusCode::INTERNAL_SERVER_ERROR, format!("{e:?}"))
@@ -1,6 +1,8 @@
 mod conversion;
 mod extension;
+mod json_feed;
 mod norm;
+mod rdf;
 
 use std::hash::Hash;
 
@@ -17,6 +19,7 @@ use crate::util::{convert_relative_url, html_body};
 
 use extension::ExtensionExt;
 
+pub use self::json_feed::{JsonAttachment, JsonAuthor, JsonFeed, JsonItem};
 pub use self::norm::{NormalizedFeed, NormalizedPost};
 
 #[allow(clippy::large_enum_variant)]
@@ -25,6 +28,7 @@ pub use self::norm::{NormalizedFeed, NormalizedPost};
 pub enum Feed {
   Rss(rss::Channel),
   Atom(atom_syndication::Feed),
+  Json(JsonFeed),
 }
 
 #[derive(
@@ -36,6 +40,8 @@ pub enum FeedFormat {
   Rss,
   /// Atom 1.0
   Atom,
+  /// JSON Feed 1.1
+  Json,
 }
 
 impl FeedFormat {
@@ -43,6 +49,7 @@ impl FeedFormat {
     match self {
       FeedFormat::Rss => "rss",
       FeedFormat::Atom => "atom",
+      FeedFormat::Json => "json",
     }
   }
 }
@@ -52,6 +59,7 @@ impl Feed {
     match self {
       Feed::Rss(_) => FeedFormat::Rss,
       Feed::Atom(_) => FeedFormat::Atom,
+      Feed::Json(_) => FeedFormat::Json,
     }
   }
 
@@ -59,6 +67,7 @@ impl Feed {
     match self {
       Feed::Rss(channel) => channel.items.len(),
       Feed::Atom(feed) => feed.entries.len(),
+      Feed::Json(feed) => feed.items.len(),
     }
   }
 
@@ -78,6 +87,22 @@ impl Feed {
         let channel: rss::Channel = W(feed).into();
         Feed::Rss(channel)
       }
+      (Feed::Rss(channel), FeedFormat::Json) => {
+        let feed: JsonFeed = W(channel).into();
+        Feed::Json(feed)
+      }
+      (Feed::Atom(feed), FeedFormat::Json) => {
+        let feed: JsonFeed = W(feed).into();
+        Feed::Json(feed)
+      }
+      (Feed::Json(feed), FeedFormat::Rss) => {
+        let channel: rss::Channel = W(feed).into();
+        Feed::Rss(channel)
+      }
+      (Feed::Json(feed), FeedFormat::Atom) => {
+        let feed: atom_syndication::Feed = W(feed).into();
+        Feed::Atom(feed)
+      }
       (original_self, _) => original_self,
     }
   }
@@ -99,6 +124,11 @@ impl Feed {
         .iter()
         .map(|entry| Post::Atom(entry.clone()).normalize())
         .collect(),
+      Feed::Json(feed) => feed
+        .items
+        .iter()
+        .map(|item| Post::Json(item.clone()).normalize())
+        .collect(),
     };
 
     NormalizedFeed {
@@ -121,8 +151,21 @@ impl Feed {
     Ok(Feed::Atom(feed))
   }
 
+  pub fn from_json_content(content: &[u8]) -> Result<Self> {
+    let feed: JsonFeed = serde_json::from_slice(content)?;
+    Ok(Feed::Json(feed))
+  }
+
+  pub fn from_rdf_content(content: &[u8]) -> Result<Self> {
+    let channel = rdf::parse_rdf(content)?;
+    Ok(Feed::Rss(channel))
+  }
+
   pub fn from_xml_content(content: &[u8]) -> Result<Self> {
-    Feed::from_rss_content(content)
+    // RDF goes first because rss::Channel accepts a literal <rdf:RDF>
+    // root but drops the Dublin Core metadata RSS 1.0 relies on.
+    Feed::from_rdf_content(content)
+      .or_else(|_| Feed::from_rss_content(content))
       .or_else(|_| Feed::from_atom_content(content))
   }
 
@@ -130,6 +173,7 @@ impl Feed {
     match self {
       Feed::Rss(_) => "application/rss+xml",
       Feed::Atom(_) => "application/atom+xml",
+      Feed::Json(_) => "application/feed+json",
     }
   }
 
@@ -158,6 +202,13 @@ impl Feed {
 
         feed.write_with_config(&mut buffer, conf)?;
       }
+      Feed::Json(feed) => {
+        if pretty {
+          serde_json::to_writer_pretty(&mut buffer, feed)?;
+        } else {
+          serde_json::to_writer(&mut buffer, feed)?;
+        }
+      }
     }
 
     let s = String::from_utf8_lossy(&buffer).into_owned();
@@ -188,6 +239,10 @@ impl Feed {
         let posts = feed.entries.split_off(0);
         posts.into_iter().map(Post::Atom).collect()
       }
+      Feed::Json(feed) => {
+        let posts = feed.items.split_off(0);
+        posts.into_iter().map(Post::Json).collect()
+      }
     }
   }
 
@@ -212,10 +267,22 @@ impl Feed {
           })
           .collect();
       }
+      Feed::Json(feed) => {
+        feed.items = posts
+          .into_iter()
+          .filter_map(|post| match post {
+            Post::Json(item) => Some(item),
+            _ => None,
+          })
+          .collect();
+      }
     }
   }
 
   pub fn merge(&mut self, other: Feed) -> Result<()> {
+    let self_format = self.format().as_str();
+    let other_format = other.format().as_str();
+
     match (self, other) {
       (Feed::Rss(channel), Feed::Rss(other)) => {
         channel.namespaces.extend(other.namespaces);
@@ -225,11 +292,11 @@ impl Feed {
         feed.namespaces.extend(other.namespaces);
         feed.entries.extend(other.entries);
       }
-      (Feed::Rss(_), _) => {
-        anyhow::bail!("cannot merge atom into rss");
+      (Feed::Json(feed), Feed::Json(other)) => {
+        feed.items.extend(other.items);
       }
-      (Feed::Atom(_), _) => {
-        anyhow::bail!("cannot merge rss into atom");
+      _ => {
+        anyhow::bail!("cannot merge {other_format} into {self_format}");
       }
     }
 
@@ -251,6 +318,11 @@ impl Feed {
           .entries
           .sort_unstable_by_key(|entry| Reverse(entry.updated));
       }
+      Feed::Json(feed) => {
+        feed
+          .items
+          .sort_unstable_by_key(|item| Reverse(json_item_timestamp(item)));
+      }
     }
   }
 
@@ -263,6 +335,9 @@ impl Feed {
       Feed::Atom(feed) => {
         feed.entries.push(post_preview.into_atom_entry());
       }
+      Feed::Json(feed) => {
+        feed.items.push(post_preview.into_json_item());
+      }
     }
   }
 }
@@ -291,9 +366,21 @@ impl TryFrom<Feed> for atom_syndication::Feed {
   }
 }
 
+#[cfg(test)]
+impl TryFrom<Feed> for JsonFeed {
+  type Error = ();
+
+  fn try_from(feed: Feed) -> Result<Self, Self::Error> {
+    match feed {
+      Feed::Json(feed) => Ok(feed),
+      _ => Err(()),
+    }
+  }
+}
+
 impl From<&FromScratch> for Feed {
   fn from(config: &FromScratch) -> Self {
-    use FeedFormat::{Atom, Rss};
+    use FeedFormat::{Atom, Json, Rss};
     match config.format {
       Rss => {
         let mut channel = rss::Channel {
@@ -328,6 +415,13 @@ impl From<&FromScratch> for Feed {
 
         Feed::Atom(feed)
       }
+      Json => {
+        let mut feed = JsonFeed::new(config.title.clone());
+        feed.home_page_url.clone_from(&config.link);
+        feed.description.clone_from(&config.description);
+
+        Feed::Json(feed)
+      }
     }
   }
 }
@@ -338,6 +432,7 @@ impl Feed {
     match self {
       Feed::Rss(channel) => &channel.title,
       Feed::Atom(feed) => feed.title.as_str(),
+      Feed::Json(feed) => &feed.title,
     }
   }
 
@@ -349,6 +444,7 @@ impl Feed {
         .first()
         .map(|link| link.href.as_str())
         .unwrap_or_default(),
+      Feed::Json(feed) => feed.home_page_url.as_deref().unwrap_or_default(),
     }
   }
 
@@ -356,6 +452,28 @@ impl Feed {
     match self {
       Feed::Rss(channel) => Some(channel.description.as_str()),
       Feed::Atom(feed) => feed.subtitle.as_ref().map(|s| s.value.as_str()),
+      Feed::Json(feed) => feed.description.as_deref(),
+    }
+  }
+
+  /// The url of the next page as advertised by the feed: a
+  /// `rel="next"` link (RFC 5005), or `next_url` in JSON Feed.
+  pub fn next_page_url(&self) -> Option<&str> {
+    match self {
+      Feed::Rss(channel) => channel
+        .extensions
+        .values()
+        .filter_map(|ext| ext.get("link"))
+        .flatten()
+        .find(|link| link.attrs.get("rel").is_some_and(|rel| rel == "next"))
+        .and_then(|link| link.attrs.get("href"))
+        .map(String::as_str),
+      Feed::Atom(feed) => feed
+        .links
+        .iter()
+        .find(|link| link.rel == "next")
+        .map(|link| link.href.as_str()),
+      Feed::Json(feed) => feed.next_url.as_deref(),
     }
   }
 }
@@ -365,6 +483,7 @@ impl Feed {
 pub enum Post {
   Rss(rss::Item),
   Atom(atom_syndication::Entry),
+  Json(JsonItem),
 }
 
 enum PostField {
@@ -404,6 +523,9 @@ impl Post {
       Post::Atom(item) => {
         item.updated = date;
       }
+      Post::Json(item) => {
+        item.date_published = Some(date.to_rfc3339());
+      }
     }
   }
 
@@ -415,6 +537,7 @@ impl Post {
         .as_ref()
         .and_then(|s| DateTime::parse_from_rfc2822(s).ok()),
       Post::Atom(item) => Some(item.updated),
+      Post::Json(item) => json_item_date(item),
     }
   }
 
@@ -430,6 +553,7 @@ impl Post {
         .iter()
         .map(|x| x.term.as_ref())
         .collect::<Vec<_>>(),
+      Post::Json(item) => item.tags.iter().map(String::as_str).collect(),
     }
   }
 
@@ -469,6 +593,12 @@ impl Post {
           .filter_map(|tag| tag.value.as_mut())
           .for_each(|v| bodies.push(v));
       }
+      Post::Json(item) => {
+        // content_text is plain text, which filters working on HTML
+        // would mangle
+        item.content_html.as_mut().map(|v| bodies.push(v));
+        item.summary.as_mut().map(|v| bodies.push(v));
+      }
     }
     bodies
   }
@@ -507,6 +637,10 @@ impl Post {
           .filter_map(|tag| tag.value.as_deref())
           .for_each(|v| bodies.push(v));
       }
+      Post::Json(item) => {
+        item.content_html.as_deref().map(|v| bodies.push(v));
+        item.summary.as_deref().map(|v| bodies.push(v));
+      }
     }
     bodies
   }
@@ -535,6 +669,16 @@ impl Post {
         item.summary = Some(atom_syndication::Text::html(String::new()));
         &mut item.summary.as_mut().unwrap().value
       }
+      Post::Json(item) => {
+        // start from the plain text content, if any, so it isn't
+        // hidden by the new HTML content
+        let html = item
+          .content_text
+          .as_deref()
+          .map(|text| htmlescape::encode_minimal(text).replace('\n', "<br>"))
+          .unwrap_or_default();
+        item.content_html.insert(html)
+      }
     }
   }
 
@@ -560,6 +704,22 @@ impl Post {
         let rss_item: rss::Item = W(item).into();
         Post::Rss(rss_item)
       }
+      (Post::Rss(item), FeedFormat::Json) => {
+        let json_item: JsonItem = W(item).into();
+        Post::Json(json_item)
+      }
+      (Post::Atom(item), FeedFormat::Json) => {
+        let json_item: JsonItem = W(item).into();
+        Post::Json(json_item)
+      }
+      (Post::Json(item), FeedFormat::Rss) => {
+        let rss_item: rss::Item = W(item).into();
+        Post::Rss(rss_item)
+      }
+      (Post::Json(item), FeedFormat::Atom) => {
+        let entry: atom_syndication::Entry = W(item).into();
+        Post::Atom(entry)
+      }
       (original_self, _) => original_self,
     }
   }
@@ -582,6 +742,12 @@ impl Post {
         item.authors.first().map(|v| v.name.as_str())
       }
       (Post::Atom(item), PostField::Guid) => Some(&item.id),
+      (Post::Json(item), PostField::Title) => item.title.as_deref(),
+      (Post::Json(item), PostField::Link) => item.url.as_deref(),
+      (Post::Json(item), PostField::Author) => {
+        item.authors.first().and_then(|v| v.name.as_deref())
+      }
+      (Post::Json(item), PostField::Guid) => Some(&item.id),
     }
   }
 
@@ -616,6 +782,18 @@ impl Post {
         }
       },
       (Post::Atom(item), PostField::Guid) => item.id = value.into(),
+      (Post::Json(item), PostField::Title) => item.title = Some(value.into()),
+      (Post::Json(item), PostField::Link) => item.url = Some(value.into()),
+      (Post::Json(item), PostField::Author) => match item.authors.get_mut(0) {
+        Some(author) => author.name = Some(value.into()),
+        None => {
+          item.authors.push(JsonAuthor {
+            name: Some(value.into()),
+            ..Default::default()
+          });
+        }
+      },
+      (Post::Json(item), PostField::Guid) => item.id = value.into(),
     }
   }
 
@@ -635,6 +813,12 @@ impl Post {
         item.authors.get_mut(0).map(|v| &mut v.name)
       }
       (Post::Atom(item), PostField::Guid) => Some(&mut item.id),
+      (Post::Json(item), PostField::Title) => item.title.as_mut(),
+      (Post::Json(item), PostField::Link) => item.url.as_mut(),
+      (Post::Json(item), PostField::Author) => {
+        item.authors.get_mut(0).and_then(|v| v.name.as_mut())
+      }
+      (Post::Json(item), PostField::Guid) => Some(&mut item.id),
     }
   }
 
@@ -680,6 +864,18 @@ impl Post {
         .name
       }
       (Post::Atom(item), PostField::Guid) => &mut item.id,
+      (Post::Json(item), PostField::Title) => {
+        item.title.get_or_insert_with(String::new)
+      }
+      (Post::Json(item), PostField::Link) => {
+        item.url.get_or_insert_with(String::new)
+      }
+      (Post::Json(item), PostField::Author) => {
+        vec_first_or_insert(&mut item.authors, JsonAuthor::default())
+          .name
+          .get_or_insert_with(String::new)
+      }
+      (Post::Json(item), PostField::Guid) => &mut item.id,
     }
   }
 }
@@ -789,6 +985,18 @@ fn rss_item_timestamp(item: &rss::Item) -> Option<i64> {
   Some(date.timestamp())
 }
 
+fn json_item_date(item: &JsonItem) -> Option<DateTime<chrono::FixedOffset>> {
+  item
+    .date_published
+    .as_ref()
+    .or(item.date_modified.as_ref())
+    .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+}
+
+fn json_item_timestamp(item: &JsonItem) -> Option<i64> {
+  json_item_date(item).map(|date| date.timestamp())
+}
+
 impl axum::response::IntoResponse for Feed {
   fn into_response(self) -> axum::response::Response {
     let content = self.serialize(true).expect("failed serializing feed");
@@ -798,3 +1006,25 @@ impl axum::response::IntoResponse for Feed {
     (http::StatusCode::OK, headers, content).into_response()
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_json_text_content_is_not_a_body() {
+    let mut post = Post::Json(JsonItem {
+      id: "1".into(),
+      content_text: Some("a < b\nc".into()),
+      ..Default::default()
+    });
+    assert!(post.bodies().is_empty());
+
+    post.ensure_body();
+    let Post::Json(item) = &post else {
+      unreachable!();
+    };
+    assert_eq!(item.content_text.as_deref(), Some("a < b\nc"));
+    assert_eq!(item.content_html.as_deref(), Some("a &lt; b<br>c"));
+  }
+}
@@ -1,5 +1,7 @@
 #![allow(clippy::field_reassign_with_default)]
 
+use super::json_feed::{JsonAttachment, JsonAuthor, JsonFeed, JsonItem};
+
 // Utility wrapper type to get around orphan rules for implementing
 // traits on foreign types.
 pub(super) struct W<T>(pub T);
@@ -293,3 +295,339 @@ impl From<W<atom_syndication::Entry>> for rss::Item {
     item
   }
 }
+
+// JSON Feed conversions. JSON Feed has no notion of namespaced
+// extensions, so those are dropped when converting into it.
+
+fn parse_rfc2822(s: &str) -> Option<atom_syndication::FixedDateTime> {
+  atom_syndication::FixedDateTime::parse_from_rfc2822(s).ok()
+}
+
+fn parse_rfc3339(s: &str) -> Option<atom_syndication::FixedDateTime> {
+  atom_syndication::FixedDateTime::parse_from_rfc3339(s).ok()
+}
+
+fn non_empty(s: String) -> Option<String> {
+  if s.is_empty() { None } else { Some(s) }
+}
+
+fn json_author(name: String) -> JsonAuthor {
+  JsonAuthor {
+    name: Some(name),
+    ..Default::default()
+  }
+}
+
+impl From<W<rss::Channel>> for JsonFeed {
+  fn from(W(channel): W<rss::Channel>) -> Self {
+    let mut feed = JsonFeed::new(channel.title);
+
+    feed.home_page_url = non_empty(channel.link);
+    feed.description = non_empty(channel.description);
+    feed.language = channel.language;
+    feed.icon = channel.image.map(|image| image.url);
+    feed.authors = channel
+      .managing_editor
+      .into_iter()
+      .map(json_author)
+      .collect();
+    feed.items = channel.items.into_iter().map(W).map(Into::into).collect();
+
+    feed
+  }
+}
+
+impl From<W<JsonFeed>> for rss::Channel {
+  fn from(W(feed): W<JsonFeed>) -> Self {
+    let mut channel = Self::default();
+
+    channel.title = feed.title;
+    channel.link = feed.home_page_url.unwrap_or_default();
+    channel.description = feed.description.unwrap_or_default();
+    channel.language = feed.language;
+    channel.managing_editor =
+      feed.authors.into_iter().find_map(|author| author.name);
+    channel.items = feed.items.into_iter().map(W).map(Into::into).collect();
+
+    channel
+  }
+}
+
+impl From<W<rss::Item>> for JsonItem {
+  fn from(W(item): W<rss::Item>) -> Self {
+    let mut json_item = JsonItem::default();
+
+    json_item.id = item
+      .guid
+      .map(|g| g.value)
+      .or_else(|| item.link.clone())
+      .unwrap_or_default();
+    json_item.url = item.link;
+    json_item.title = item.title;
+
+    // RSS description usually carries the post body, unless a
+    // separate content:encoded is present.
+    if item.content.is_some() {
+      json_item.content_html = item.content;
+      json_item.summary = item.description;
+    } else {
+      json_item.content_html = item.description;
+    }
+
+    json_item.date_published = item
+      .pub_date
+      .as_deref()
+      .and_then(parse_rfc2822)
+      .map(|d| d.to_rfc3339());
+    json_item.authors = item.author.into_iter().map(json_author).collect();
+    json_item.tags = item.categories.into_iter().map(|c| c.name).collect();
+    json_item.attachments = item
+      .enclosure
+      .into_iter()
+      .map(|e| JsonAttachment {
+        size_in_bytes: e.length.parse().ok(),
+        url: e.url,
+        mime_type: e.mime_type,
+        ..Default::default()
+      })
+      .collect();
+
+    json_item
+  }
+}
+
+impl From<W<JsonItem>> for rss::Item {
+  fn from(W(json_item): W<JsonItem>) -> Self {
+    let mut item = rss::Item::default();
+
+    item.title = json_item.title;
+    item.guid = Some(rss::Guid {
+      value: json_item.id,
+      permalink: false,
+    });
+    item.link = json_item.url;
+
+    if json_item.summary.is_some() {
+      item.description = json_item.summary;
+      item.content = json_item.content_html;
+    } else {
+      item.description = json_item.content_html.or(json_item.content_text);
+    }
+
+    item.pub_date = json_item
+      .date_published
+      .or(json_item.date_modified)
+      .as_deref()
+      .and_then(parse_rfc3339)
+      .map(|d| d.to_rfc2822());
+    item.author = json_item.authors.into_iter().find_map(|a| a.name);
+    item.categories = json_item
+      .tags
+      .into_iter()
+      .map(|name| rss::Category {
+        name,
+        ..Default::default()
+      })
+      .collect();
+    item.enclosure =
+      json_item
+        .attachments
+        .into_iter()
+        .next()
+        .map(|a| rss::Enclosure {
+          url: a.url,
+          mime_type: a.mime_type,
+          length: a.size_in_bytes.unwrap_or_default().to_string(),
+        });
+
+    item
+  }
+}
+
+impl From<W<atom_syndication::Feed>> for JsonFeed {
+  fn from(W(atom_feed): W<atom_syndication::Feed>) -> Self {
+    let mut feed = JsonFeed::new(atom_feed.title.value);
+
+    feed.home_page_url = atom_feed.links.into_iter().next().map(|l| l.href);
+    feed.description = atom_feed.subtitle.map(|s| s.value);
+    feed.language = atom_feed.lang;
+    // atom logo and icon map to JSON Feed icon and favicon respectively
+    feed.icon = atom_feed.logo;
+    feed.favicon = atom_feed.icon;
+    feed.authors = atom_feed
+      .authors
+      .into_iter()
+      .map(|p| JsonAuthor {
+        name: Some(p.name),
+        url: p.uri,
+        avatar: None,
+      })
+      .collect();
+    feed.items = atom_feed
+      .entries
+      .into_iter()
+      .map(W)
+      .map(Into::into)
+      .collect();
+
+    feed
+  }
+}
+
+impl From<W<JsonFeed>> for atom_syndication::Feed {
+  fn from(W(feed): W<JsonFeed>) -> Self {
+    use atom_syndication::{Link, Person, Text};
+
+    let mut atom_feed = Self::default();
+
+    atom_feed.title = Text::plain(feed.title);
+    atom_feed.id = feed
+      .feed_url
+      .clone()
+      .or_else(|| feed.home_page_url.clone())
+      .unwrap_or_default();
+    if let Some(href) = feed.home_page_url {
+      atom_feed.links.push(Link {
+        href,
+        ..Default::default()
+      });
+    }
+    atom_feed.subtitle = feed.description.map(Text::plain);
+    atom_feed.lang = feed.language;
+    atom_feed.logo = feed.icon;
+    atom_feed.icon = feed.favicon;
+    atom_feed.authors = feed
+      .authors
+      .into_iter()
+      .filter_map(|a| {
+        Some(Person {
+          name: a.name?,
+          uri: a.url,
+          ..Default::default()
+        })
+      })
+      .collect();
+
+    atom_feed.entries = feed.items.into_iter().map(W).map(Into::into).collect();
+    atom_feed.updated = atom_feed
+      .entries
+      .iter()
+      .map(|e| e.updated)
+      .max()
+      .unwrap_or_default();
+
+    atom_feed
+  }
+}
+
+impl From<W<atom_syndication::Entry>> for JsonItem {
+  fn from(W(entry): W<atom_syndication::Entry>) -> Self {
+    let mut json_item = JsonItem::default();
+
+    json_item.id = entry.id;
+    json_item.title = Some(entry.title.value);
+    json_item.content_html = entry.content.and_then(|c| c.value);
+    json_item.summary = entry.summary.map(|s| s.value);
+    json_item.date_published = entry.published.map(|d| d.to_rfc3339());
+    if entry.updated.timestamp() != 0 {
+      json_item.date_modified = Some(entry.updated.to_rfc3339());
+    }
+    json_item.authors = entry
+      .authors
+      .into_iter()
+      .map(|p| JsonAuthor {
+        name: Some(p.name),
+        url: p.uri,
+        avatar: None,
+      })
+      .collect();
+    json_item.tags = entry.categories.into_iter().map(|c| c.term).collect();
+
+    for link in entry.links {
+      if link.rel == "enclosure" {
+        json_item.attachments.push(JsonAttachment {
+          url: link.href,
+          mime_type: link.mime_type.unwrap_or_default(),
+          title: link.title,
+          size_in_bytes: link.length.and_then(|l| l.parse().ok()),
+          duration_in_seconds: None,
+        });
+      } else if json_item.url.is_none() {
+        json_item.url = Some(link.href);
+      }
+    }
+
+    json_item
+  }
+}
+
+impl From<W<JsonItem>> for atom_syndication::Entry {
+  fn from(W(json_item): W<JsonItem>) -> Self {
+    use atom_syndication::{Category, Content, Entry, Link, Person, Text};
+
+    let mut entry = Entry::default();
+
+    entry.id = json_item.id;
+    entry.title = json_item.title.map(Text::plain).unwrap_or_default();
+
+    let published = json_item.date_published.as_deref().and_then(parse_rfc3339);
+    let modified = json_item.date_modified.as_deref().and_then(parse_rfc3339);
+    entry.published = published;
+    entry.updated = modified.or(published).unwrap_or_default();
+
+    if let Some(href) = json_item.url {
+      entry.links.push(Link {
+        href,
+        ..Default::default()
+      });
+    }
+    for attachment in json_item.attachments {
+      entry.links.push(Link {
+        href: attachment.url,
+        rel: "enclosure".into(),
+        mime_type: Some(attachment.mime_type),
+        title: attachment.title,
+        length: attachment.size_in_bytes.map(|s| s.to_string()),
+        ..Default::default()
+      });
+    }
+
+    entry.authors = json_item
+      .authors
+      .into_iter()
+      .filter_map(|a| {
+        Some(Person {
+          name: a.name?,
+          uri: a.url,
+          ..Default::default()
+        })
+      })
+      .collect();
+    entry.categories = json_item
+      .tags
+      .into_iter()
+      .map(|term| Category {
+        term,
+        ..Default::default()
+      })
+      .collect();
+
+    entry.summary = json_item.summary.map(Into::into);
+    entry.content = json_item
+      .content_html
+      .map(|html| Content {
+        value: Some(html),
+        content_type: Some("html".into()),
+        ..Default::default()
+      })
+      .or_else(|| {
+        json_item.content_text.map(|text| Content {
+          value: Some(text),
+          content_type: Some("text".into()),
+          ..Default::default()
+        })
+      });
+
+    entry
+  }
+}
@@ -0,0 +1,150 @@
+//! Data model for [JSON Feed 1.1](https://www.jsonfeed.org/version/1.1/).
+//!
+//! Neither rss nor atom_syndication covers this format, so the
+//! structs below follow the spec directly. Unknown keys, including
+//! extension fields prefixed with an underscore, are kept in
+//! `extensions` so they survive a round trip.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+pub const JSON_FEED_VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct JsonFeed {
+  pub version: String,
+  pub title: String,
+  #[serde(default)]
+  pub home_page_url: Option<String>,
+  #[serde(default)]
+  pub feed_url: Option<String>,
+  #[serde(default)]
+  pub description: Option<String>,
+  #[serde(default)]
+  pub user_comment: Option<String>,
+  #[serde(default)]
+  pub next_url: Option<String>,
+  #[serde(default)]
+  pub icon: Option<String>,
+  #[serde(default)]
+  pub favicon: Option<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub authors: Vec<JsonAuthor>,
+  #[serde(default)]
+  pub language: Option<String>,
+  #[serde(default)]
+  pub expired: Option<bool>,
+  #[serde(default)]
+  pub items: Vec<JsonItem>,
+  #[serde(flatten)]
+  pub extensions: BTreeMap<String, serde_json::Value>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct JsonItem {
+  #[serde(deserialize_with = "string_or_number")]
+  pub id: String,
+  #[serde(default)]
+  pub url: Option<String>,
+  #[serde(default)]
+  pub external_url: Option<String>,
+  #[serde(default)]
+  pub title: Option<String>,
+  #[serde(default)]
+  pub content_html: Option<String>,
+  #[serde(default)]
+  pub content_text: Option<String>,
+  #[serde(default)]
+  pub summary: Option<String>,
+  #[serde(default)]
+  pub image: Option<String>,
+  #[serde(default)]
+  pub banner_image: Option<String>,
+  #[serde(default)]
+  pub date_published: Option<String>,
+  #[serde(default)]
+  pub date_modified: Option<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub authors: Vec<JsonAuthor>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub tags: Vec<String>,
+  #[serde(default)]
+  pub language: Option<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub attachments: Vec<JsonAttachment>,
+  #[serde(flatten)]
+  pub extensions: BTreeMap<String, serde_json::Value>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct JsonAuthor {
+  #[serde(default)]
+  pub name: Option<String>,
+  #[serde(default)]
+  pub url: Option<String>,
+  #[serde(default)]
+  pub avatar: Option<String>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct JsonAttachment {
+  pub url: String,
+  pub mime_type: String,
+  #[serde(default)]
+  pub title: Option<String>,
+  #[serde(default)]
+  pub size_in_bytes: Option<u64>,
+  #[serde(default)]
+  pub duration_in_seconds: Option<f64>,
+}
+
+// The spec requires a numeric id to be coerced to a string.
+fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Id {
+    String(String),
+    Number(serde_json::Number),
+  }
+
+  Ok(match Id::deserialize(deserializer)? {
+    Id::String(id) => id,
+    Id::Number(id) => id.to_string(),
+  })
+}
+
+impl JsonFeed {
+  pub fn new(title: String) -> Self {
+    Self {
+      version: JSON_FEED_VERSION.to_owned(),
+      title,
+      ..Default::default()
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_numeric_item_id() {
+    let content = r#"{
+      "version": "https://jsonfeed.org/version/1.1",
+      "title": "Numbers",
+      "items": [{ "id": 42 }, { "id": "43" }]
+    }"#;
+
+    let feed: JsonFeed = serde_json::from_str(content).unwrap();
+    assert_eq!(feed.items[0].id, "42");
+    assert_eq!(feed.items[1].id, "43");
+  }
+}
@@ -1,6 +1,8 @@
 use chrono::{DateTime, FixedOffset};
 use serde::Serialize;
 
+use super::json_feed::{JsonAuthor, JsonItem};
+
 #[derive(Debug, Clone, Serialize, Hash, PartialEq, Eq, Default)]
 pub struct NormalizedFeed {
   pub title: String,
@@ -30,7 +32,9 @@ impl NormalizedPost {
       title: Some(self.title),
       link: Some(self.link),
       description: self.body,
-      pub_date: self.date.map(|d| d.to_rfc3339()),
+      // RSS 2.0 requires RFC 822 dates, which readers may not accept in
+      // other formats
+      pub_date: self.date.map(|d| d.to_rfc2822()),
       author: self.author,
       guid: Some(guid),
       ..Default::default()
@@ -64,4 +68,42 @@ impl NormalizedPost {
       ..Default::default()
     }
   }
+
+  pub fn into_json_item(self) -> JsonItem {
+    JsonItem {
+      id: self.link.clone(),
+      url: Some(self.link),
+      title: Some(self.title),
+      content_html: self.body,
+      date_published: self.date.map(|d| d.to_rfc3339()),
+      authors: self
+        .author
+        .into_iter()
+        .map(|a| JsonAuthor {
+          name: Some(a),
+          ..Default::default()
+        })
+        .collect(),
+      tags: self.categories,
+      ..Default::default()
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_rss_item_date() {
+    let post = NormalizedPost {
+      date: DateTime::parse_from_rfc3339("2024-01-02T10:00:00+01:00").ok(),
+      ..Default::default()
+    };
+    let item = post.into_rss_item();
+    assert_eq!(
+      item.pub_date.as_deref(),
+      Some("Tue, 2 Jan 2024 10:00:00 +0100")
+    );
+  }
 }
@@ -0,0 +1,329 @@
+//! Reader for [RSS 1.0](https://web.resource.org/rss/1.0/spec) (RDF Site
+//! Summary) documents.
+//!
+//! RSS 1.0 is an RDF vocabulary rather than a revision of RSS 2.0: the
+//! items sit next to `<channel>` under `<rdf:RDF>`, and most metadata
+//! comes from the Dublin Core module. The document is mapped into an
+//! `rss::Channel` so it behaves like any other RSS feed downstream.
+
+use chrono::{DateTime, NaiveDate};
+use quick_xml::NsReader;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::ResolveResult;
+use rss::extension::dublincore::{self, DublinCoreExtension};
+
+use crate::error::Result;
+
+const RDF_NAMESPACE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const CONTENT_NAMESPACE: &str = "http://purl.org/rss/1.0/modules/content/";
+const RSS_NAMESPACES: [&str; 2] = [
+  "http://purl.org/rss/1.0/",
+  // RSS 0.90
+  "http://my.netscape.com/rdf/simple/0.9/",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ns {
+  Rdf,
+  Dc,
+  Content,
+  // RSS 1.0 and 0.90 elements
+  Rss,
+  Other,
+}
+
+#[derive(Default)]
+struct Entry {
+  about: Option<String>,
+  title: Option<String>,
+  link: Option<String>,
+  description: Option<String>,
+  content: Option<String>,
+  dc: DublinCoreExtension,
+}
+
+enum Block {
+  Channel,
+  Item,
+  Ignored,
+}
+
+pub fn parse_rdf(content: &[u8]) -> Result<rss::Channel> {
+  let mut reader = NsReader::from_reader(content);
+  reader.trim_text(true).expand_empty_elements(true);
+
+  let mut channel = Entry::default();
+  let mut items: Vec<Entry> = vec![];
+  let mut found_root = false;
+
+  // the block under <rdf:RDF> we are in, and the element inside it
+  // whose text we are collecting
+  let mut block: Option<Block> = None;
+  let mut field: Option<(Ns, String)> = None;
+  let mut text = String::new();
+  let mut depth = 0usize;
+
+  loop {
+    let (ns, event) = reader.read_resolved_event()?;
+    match event {
+      Event::Start(e) => {
+        depth += 1;
+        let ns = classify(&ns);
+        let name = local_name(&e);
+
+        match depth {
+          1 if ns == Ns::Rdf && name == "RDF" => found_root = true,
+          1 => anyhow::bail!("not an RDF document: root element <{name}>"),
+          2 => {
+            let (entry, b) = match name.as_str() {
+              "channel" => (Some(&mut channel), Block::Channel),
+              "item" => {
+                items.push(Entry::default());
+                (items.last_mut(), Block::Item)
+              }
+              _ => (None, Block::Ignored),
+            };
+            if let Some(entry) = entry {
+              entry.about = rdf_about(&reader, &e)?;
+            }
+            block = Some(b);
+          }
+          3 => {
+            field = Some((ns, name));
+            text.clear();
+          }
+          _ => {}
+        }
+      }
+      Event::Text(t) if depth == 3 => text.push_str(&t.unescape()?),
+      Event::CData(t) if depth == 3 => {
+        text.push_str(&String::from_utf8_lossy(&t.into_inner()))
+      }
+      Event::End(_) => {
+        if depth == 3 {
+          let entry = match block {
+            Some(Block::Channel) => Some(&mut channel),
+            Some(Block::Item) => items.last_mut(),
+            _ => None,
+          };
+          if let (Some(entry), Some((ns, name))) = (entry, field.take()) {
+            entry.set(ns, &name, std::mem::take(&mut text));
+          }
+        }
+        if depth == 2 {
+          block = None;
+        }
+        depth = depth.saturating_sub(1);
+      }
+      Event::Eof => break,
+      _ => {}
+    }
+  }
+
+  if !found_root {
+    anyhow::bail!("not an RDF document: missing <rdf:RDF> element");
+  }
+
+  let mut channel = channel.into_channel();
+  channel.items = items.into_iter().map(Entry::into_item).collect();
+  channel
+    .namespaces
+    .insert("dc".into(), dublincore::NAMESPACE.into());
+  Ok(channel)
+}
+
+fn classify(ns: &ResolveResult) -> Ns {
+  let ResolveResult::Bound(ns) = ns else {
+    return Ns::Other;
+  };
+
+  match ns.as_ref() {
+    n if n == RDF_NAMESPACE.as_bytes() => Ns::Rdf,
+    n if n == dublincore::NAMESPACE.as_bytes() => Ns::Dc,
+    n if n == CONTENT_NAMESPACE.as_bytes() => Ns::Content,
+    n if RSS_NAMESPACES.iter().any(|rss| n == rss.as_bytes()) => Ns::Rss,
+    _ => Ns::Other,
+  }
+}
+
+fn local_name(e: &BytesStart) -> String {
+  String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+}
+
+fn rdf_about(
+  reader: &NsReader<&[u8]>,
+  e: &BytesStart,
+) -> Result<Option<String>> {
+  for attr in e.attributes() {
+    let attr = attr?;
+    let (ns, name) = reader.resolve_attribute(attr.key);
+    if classify(&ns) == Ns::Rdf && name.as_ref() == b"about" {
+      return Ok(Some(attr.unescape_value()?.into_owned()));
+    }
+  }
+  Ok(None)
+}
+
+// W3CDTF, the profile of ISO 8601 used by dc:date, allows omitting
+// the time part entirely.
+fn w3cdtf_to_rfc2822(s: &str) -> Option<String> {
+  if let Ok(date) = DateTime::parse_from_rfc3339(s) {
+    return Some(date.to_rfc2822());
+  }
+
+  let date = NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?;
+  Some(date.and_hms_opt(0, 0, 0)?.and_utc().to_rfc2822())
+}
+
+impl Entry {
+  fn set(&mut self, ns: Ns, name: &str, value: String) {
+    let dc = &mut self.dc;
+    let slot = match (ns, name) {
+      (Ns::Content, "encoded") => {
+        self.content = Some(value);
+        return;
+      }
+      (Ns::Dc, "contributor") => &mut dc.contributors,
+      (Ns::Dc, "coverage") => &mut dc.coverages,
+      (Ns::Dc, "creator") => &mut dc.creators,
+      (Ns::Dc, "date") => &mut dc.dates,
+      (Ns::Dc, "description") => &mut dc.descriptions,
+      (Ns::Dc, "format") => &mut dc.formats,
+      (Ns::Dc, "identifier") => &mut dc.identifiers,
+      (Ns::Dc, "language") => &mut dc.languages,
+      (Ns::Dc, "publisher") => &mut dc.publishers,
+      (Ns::Dc, "relation") => &mut dc.relations,
+      (Ns::Dc, "rights") => &mut dc.rights,
+      (Ns::Dc, "source") => &mut dc.sources,
+      (Ns::Dc, "subject") => &mut dc.subjects,
+      (Ns::Dc, "title") => &mut dc.titles,
+      (Ns::Dc, "type") => &mut dc.types,
+      (Ns::Rss, "title") => {
+        self.title = Some(value);
+        return;
+      }
+      (Ns::Rss, "link") => {
+        self.link = Some(value);
+        return;
+      }
+      (Ns::Rss, "description") => {
+        self.description = Some(value);
+        return;
+      }
+      _ => return,
+    };
+    slot.push(value);
+  }
+
+  fn dc_ext(&self) -> Option<DublinCoreExtension> {
+    if self.dc == DublinCoreExtension::default() {
+      None
+    } else {
+      Some(self.dc.clone())
+    }
+  }
+
+  fn pub_date(&self) -> Option<String> {
+    self.dc.dates.first().and_then(|d| w3cdtf_to_rfc2822(d))
+  }
+
+  fn into_channel(self) -> rss::Channel {
+    let dc = &self.dc;
+    rss::Channel {
+      title: self.title.clone().unwrap_or_default(),
+      link: self.link.clone().or(self.about.clone()).unwrap_or_default(),
+      description: self.description.clone().unwrap_or_default(),
+      language: dc.languages.first().cloned(),
+      copyright: dc.rights.first().cloned(),
+      managing_editor: dc.creators.first().cloned(),
+      pub_date: self.pub_date(),
+      categories: dc.subjects.iter().map(|s| category(s)).collect(),
+      dublin_core_ext: self.dc_ext(),
+      ..Default::default()
+    }
+  }
+
+  fn into_item(self) -> rss::Item {
+    let dc = &self.dc;
+    let link = self.link.clone().or(self.about.clone());
+    let guid = self.about.clone().or(link.clone()).map(|value| rss::Guid {
+      permalink: Some(&value) == link.as_ref(),
+      value,
+    });
+
+    rss::Item {
+      title: self.title.clone(),
+      link,
+      description: self.description.clone(),
+      content: self.content.clone(),
+      author: dc.creators.first().cloned(),
+      pub_date: self.pub_date(),
+      categories: dc.subjects.iter().map(|s| category(s)).collect(),
+      guid,
+      dublin_core_ext: self.dc_ext(),
+      ..Default::default()
+    }
+  }
+}
+
+fn category(name: &str) -> rss::Category {
+  rss::Category {
+    name: name.to_owned(),
+    domain: None,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_parse_rdf() {
+    let content = include_bytes!("../../fixtures/sample_rdf.xml");
+    let channel = parse_rdf(content).unwrap();
+
+    assert_eq!(channel.title, "Example RDF Feed");
+    assert_eq!(channel.link, "https://example.com/");
+    assert_eq!(channel.language.as_deref(), Some("en-us"));
+    assert_eq!(channel.items.len(), 2);
+
+    let item = &channel.items[0];
+    assert_eq!(item.title.as_deref(), Some("First article"));
+    assert_eq!(item.link.as_deref(), Some("https://example.com/first"));
+    assert_eq!(item.author.as_deref(), Some("Jane Doe"));
+    assert_eq!(
+      item.pub_date.as_deref(),
+      Some("Tue, 2 Jan 2024 10:00:00 +0000")
+    );
+    assert_eq!(item.categories[0].name, "science");
+    assert_eq!(item.content.as_deref(), Some("<p>Full content</p>"));
+    assert!(item.guid.as_ref().unwrap().permalink);
+
+    let item = &channel.items[1];
+    assert_eq!(
+      item.pub_date.as_deref(),
+      Some("Mon, 1 Jan 2024 00:00:00 +0000")
+    );
+    assert_eq!(item.description.as_deref(), Some("Second & last"));
+  }
+
+  #[test]
+  fn test_ignore_foreign_elements() {
+    let content = br#"<?xml version="1.0"?>
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+  xmlns="http://purl.org/rss/1.0/" xmlns:x="https://example.com/x">
+  <item rdf:about="https://example.com/a">
+    <title>Real title</title>
+    <x:title>Other title</x:title>
+  </item>
+</rdf:RDF>"#;
+    let channel = parse_rdf(content).unwrap();
+    assert_eq!(channel.items[0].title.as_deref(), Some("Real title"));
+  }
+
+  #[test]
+  fn test_reject_non_rdf() {
+    let content = include_bytes!("../../fixtures/minimal_rss_20.xml");
+    assert!(parse_rdf(content).is_err());
+  }
+}
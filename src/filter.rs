@@ -1,4 +1,6 @@
 pub(crate) mod convert;
+pub(crate) mod dedupe;
+pub(crate) mod expr;
 pub(crate) mod full_text;
 pub(crate) mod highlight;
 pub(crate) mod html;
@@ -13,6 +15,8 @@ pub(crate) mod note;
 pub(crate) mod sanitize;
 pub(crate) mod select;
 pub(crate) mod simplify_html;
+pub(crate) mod sort;
+pub(crate) mod when;
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -337,6 +341,9 @@ define_filters!(
   Note => note::NoteFilterConfig, "Add non-functional comment";
   ConvertTo => convert::ConvertToConfig, "Convert feed to another format";
   Limit => limit::LimitConfig, "Limit the number of posts";
+  Dedupe => dedupe::DedupeConfig, "Remove duplicate posts";
+  Sort => sort::SortConfig, "Sort posts by date, title or other keys";
+  When => when::WhenConfig, "Apply filters only to matching posts";
   Magnet => magnet::MagnetConfig, "Find magnet links in posts";
   ImageProxy => image_proxy::Config, "Rewrite image src to use proxy";
   InjectCss => inject_css::InjectCssConfig, "Inject CSS styles into post bodies";
@@ -102,6 +102,83 @@ mod tests {
     assert_eq!(feed_1, feed_2);
   }
 
+  #[tokio::test]
+  async fn test_convert_from_json_feed() {
+    let config = r"
+      !endpoint
+      path: /feed.xml
+      source: fixture:///sample_json_feed.json?content_type=application/feed%2Bjson
+      filters:
+        - convert_to: rss
+    ";
+
+    let feed = fetch_endpoint(config, "").await;
+    let channel: rss::Channel = feed.try_into().unwrap();
+
+    assert_eq!(channel.title, "Example JSON Feed");
+    assert_eq!(channel.link, "https://example.org/");
+    assert_eq!(channel.items.len(), 2);
+
+    let item = &channel.items[0];
+    assert_eq!(item.title.as_deref(), Some("Second item"));
+    assert_eq!(
+      item.description.as_deref(),
+      Some("<p>Hello, <b>world</b>!</p>")
+    );
+    assert_eq!(item.categories.len(), 2);
+    assert!(item.pub_date.is_some());
+
+    let item = &channel.items[1];
+    assert_eq!(item.author.as_deref(), Some("John Doe"));
+    assert_eq!(item.description.as_deref(), Some("First!"));
+    assert_eq!(
+      item.enclosure.as_ref().map(|e| e.length.as_str()),
+      Some("12345")
+    );
+  }
+
+  #[tokio::test]
+  async fn test_rss_to_json_to_rss() {
+    let config_1 = r"
+      !endpoint
+      path: /feed.xml
+      source: fixture:///minimal_rss_20.xml
+      filters:
+        - convert_to: json
+        - convert_to: rss
+    ";
+    let config_2 = r"
+      !endpoint
+      path: /feed.xml
+      source: fixture:///minimal_rss_20.xml
+      filters: []
+    ";
+
+    let feed_1 = fetch_endpoint(config_1, "").await;
+    let feed_2 = fetch_endpoint(config_2, "").await;
+    assert_eq!(feed_1, feed_2);
+  }
+
+  #[tokio::test]
+  async fn test_json_feed_round_trip() {
+    let config = r"
+      !endpoint
+      path: /feed.json
+      source: fixture:///sample_json_feed.json?content_type=application/feed%2Bjson
+      filters: []
+    ";
+
+    let feed = fetch_endpoint(config, "").await;
+    assert_eq!(feed.format(), FeedFormat::Json);
+
+    let feed: crate::feed::JsonFeed = feed.try_into().unwrap();
+    assert_eq!(feed.items.len(), 2);
+    assert_eq!(
+      feed.items[0].extensions.get("_custom"),
+      Some(&serde_json::json!({ "about": "https://example.org/ext" }))
+    );
+  }
+
   #[tokio::test]
   async fn test_atom_to_rss_to_atom() {
     let config_1 = r"
@@ -0,0 +1,321 @@
+use std::{
+  collections::{HashMap, HashSet},
+  hash::{Hash, Hasher},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+  error::Result,
+  feed::{Feed, Post},
+};
+
+use super::{FeedFilter, FeedFilterConfig, FilterContext};
+
+// tracking parameters that don't change the linked page
+const TRACKING_PARAMS: [&str; 4] = ["fbclid", "gclid", "mc_cid", "mc_eid"];
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+pub struct DedupeConfig {
+  /// The fields identifying a post. Posts are duplicates if any of
+  /// the fields are equal after normalization. (Default: [guid, link])
+  #[serde(default = "default_keys")]
+  by: Vec<DedupeKey>,
+  /// Also treat posts as duplicates if the similarity of their bodies
+  /// is at least this value, from 0.0 to 1.0 (e.g. 0.8)
+  #[serde(default)]
+  similarity: Option<Similarity>,
+  /// Which of the duplicates to keep (Default: first)
+  #[serde(default)]
+  keep: KeepPolicy,
+}
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupeKey {
+  /// The guid (or id) of the post
+  Guid,
+  /// The link, without tracking parameters (utm_*, fbclid, etc.),
+  /// fragment, scheme, "www." prefix and trailing slash
+  Link,
+  /// The title, ignoring case, punctuation and whitespace
+  Title,
+}
+
+#[derive(
+  JsonSchema,
+  Serialize,
+  Deserialize,
+  Clone,
+  Copy,
+  Debug,
+  Default,
+  PartialEq,
+  Eq,
+  Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum KeepPolicy {
+  /// The first post in the feed
+  #[default]
+  First,
+  /// The post with the latest publication date
+  Newest,
+  /// The post with the longest body
+  Longest,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(transparent)]
+pub struct Similarity(f64);
+
+// configs need to be hashable, which floats aren't
+impl PartialEq for Similarity {
+  fn eq(&self, other: &Self) -> bool {
+    self.0.to_bits() == other.0.to_bits()
+  }
+}
+
+impl Eq for Similarity {}
+
+impl Hash for Similarity {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.0.to_bits().hash(state);
+  }
+}
+
+fn default_keys() -> Vec<DedupeKey> {
+  vec![DedupeKey::Guid, DedupeKey::Link]
+}
+
+pub struct Dedupe {
+  config: DedupeConfig,
+}
+
+#[async_trait::async_trait]
+impl FeedFilterConfig for DedupeConfig {
+  type Filter = Dedupe;
+
+  async fn build(self) -> Result<Self::Filter> {
+    if let Some(Similarity(similarity)) = self.similarity
+      && !(0.0..=1.0).contains(&similarity)
+    {
+      anyhow::bail!("similarity must be between 0.0 and 1.0");
+    }
+
+    Ok(Dedupe { config: self })
+  }
+}
+
+// posts considered the same
+struct Group {
+  kept: Post,
+  shingles: Vec<HashSet<String>>,
+}
+
+#[async_trait::async_trait]
+impl FeedFilter for Dedupe {
+  async fn run(
+    &self,
+    _ctx: &mut FilterContext,
+    mut feed: Feed,
+  ) -> Result<Feed> {
+    let mut groups: Vec<Group> = vec![];
+    let mut group_by_key: HashMap<(DedupeKey, String), usize> = HashMap::new();
+
+    for post in feed.take_posts() {
+      let keys: Vec<_> = self
+        .config
+        .by
+        .iter()
+        .filter_map(|&key| Some((key, normalized_key(&post, key)?)))
+        .collect();
+      let shingles = self.config.similarity.map(|_| body_shingles(&post));
+
+      let found = keys
+        .iter()
+        .find_map(|key| group_by_key.get(key).copied())
+        .or_else(|| self.find_similar(&groups, shingles.as_ref()?));
+
+      let index = match found {
+        Some(index) => {
+          let group = &mut groups[index];
+          if self.config.keep.prefers(&post, &group.kept) {
+            group.kept = post;
+          }
+          group.shingles.extend(shingles);
+          index
+        }
+        None => {
+          groups.push(Group {
+            kept: post,
+            shingles: shingles.into_iter().collect(),
+          });
+          groups.len() - 1
+        }
+      };
+
+      for key in keys {
+        group_by_key.entry(key).or_insert(index);
+      }
+    }
+
+    feed.set_posts(groups.into_iter().map(|g| g.kept).collect());
+    Ok(feed)
+  }
+}
+
+impl Dedupe {
+  fn find_similar(
+    &self,
+    groups: &[Group],
+    shingles: &HashSet<String>,
+  ) -> Option<usize> {
+    let Similarity(threshold) = self.config.similarity?;
+    if shingles.is_empty() {
+      return None;
+    }
+
+    groups.iter().position(|group| {
+      group
+        .shingles
+        .iter()
+        .any(|other| jaccard(shingles, other) >= threshold)
+    })
+  }
+}
+
+impl KeepPolicy {
+  // whether the post should replace the one kept so far
+  fn prefers(&self, post: &Post, kept: &Post) -> bool {
+    match self {
+      Self::First => false,
+      Self::Newest => post.pub_date() > kept.pub_date(),
+      Self::Longest => body_len(post) > body_len(kept),
+    }
+  }
+}
+
+fn body_len(post: &Post) -> usize {
+  post.first_body().map_or(0, str::len)
+}
+
+fn normalized_key(post: &Post, key: DedupeKey) -> Option<String> {
+  let value = match key {
+    DedupeKey::Guid => post.guid()?.trim().to_owned(),
+    DedupeKey::Link => normalize_link(post.link()?),
+    DedupeKey::Title => words(post.title()?).join(" "),
+  };
+
+  (!value.is_empty()).then_some(value)
+}
+
+fn normalize_link(link: &str) -> String {
+  let Ok(url) = Url::parse(link.trim()) else {
+    return link.trim().trim_end_matches('/').to_owned();
+  };
+
+  let host = url.host_str().unwrap_or_default();
+  let host = host.strip_prefix("www.").unwrap_or(host);
+  let path = url.path().trim_end_matches('/');
+  let mut query: Vec<_> = url
+    .query_pairs()
+    .filter(|(name, _)| {
+      !name.starts_with("utm_") && !TRACKING_PARAMS.contains(&name.as_ref())
+    })
+    .map(|(name, value)| format!("{name}={value}"))
+    .collect();
+  query.sort();
+
+  if query.is_empty() {
+    format!("{host}{path}")
+  } else {
+    format!("{host}{path}?{}", query.join("&"))
+  }
+}
+
+fn words(text: &str) -> Vec<String> {
+  text
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .map(str::to_lowercase)
+    .collect()
+}
+
+// the overlapping sequences of three words in the body text
+fn body_shingles(post: &Post) -> HashSet<String> {
+  let Some(body) = post.first_body() else {
+    return HashSet::new();
+  };
+  let html = scraper::Html::parse_fragment(body);
+  let text: String = html.root_element().text().collect::<Vec<_>>().join(" ");
+  let words = words(&text);
+
+  if words.len() < 3 {
+    return HashSet::from_iter((!words.is_empty()).then(|| words.join(" ")));
+  }
+  words.windows(3).map(|w| w.join(" ")).collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+  let intersection = a.intersection(b).count();
+  let union = a.len() + b.len() - intersection;
+  intersection as f64 / union as f64
+}
+
+#[cfg(test)]
+mod test {
+  use crate::test_utils::fetch_duplicates_titles;
+
+  #[tokio::test]
+  async fn test_dedupe_by_guid_and_link() {
+    assert_eq!(
+      fetch_duplicates_titles("dedupe", "{}").await,
+      ["Big News", "Another story", "Another story, mirrored"]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_dedupe_keep_policy() {
+    assert_eq!(
+      fetch_duplicates_titles("dedupe", "{ keep: newest }").await,
+      [
+        "Big news!",
+        "Another story (updated)",
+        "Another story, mirrored"
+      ]
+    );
+    assert_eq!(
+      fetch_duplicates_titles("dedupe", "{ keep: longest }").await,
+      ["Big news!", "Another story", "Another story, mirrored"]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_dedupe_by_title() {
+    assert_eq!(
+      fetch_duplicates_titles("dedupe", "{ by: [title] }").await,
+      [
+        "Big News",
+        "Another story",
+        "Another story, mirrored",
+        "Another story (updated)"
+      ]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_dedupe_by_similarity() {
+    assert_eq!(
+      fetch_duplicates_titles("dedupe", "{ by: [link], similarity: 0.8 }")
+        .await,
+      ["Big News", "Another story", "Another story (updated)"]
+    );
+  }
+}
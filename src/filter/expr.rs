@@ -0,0 +1,501 @@
+//! A small boolean expression language for selecting posts, e.g.
+//!
+//! ```text
+//! title ~ "rust" and not author == "bot" and date > -7d
+//!   and category in [news, release]
+//! ```
+//!
+//! Conditions compare a field of the post with a value:
+//!
+//! - `title`, `body`, `link`, `author`, `guid`, `category` and `any`
+//!   (title, body or category) support `~` and `!~` (regex match),
+//!   `==` and `!=`, and `in [a, b]`. Comparisons ignore case, and a
+//!   condition holds if any of the values of the field satisfies it.
+//! - `date` supports `<`, `<=`, `>`, `>=`, `==` and `!=` against a
+//!   date (`2024-01-31`) or a duration relative to now (`-7d`).
+//!
+//! Conditions are combined with `and`, `or`, `not` and parentheses.
+//! Values are quoted with `"` or `'` unless they are a single word.
+
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+use regex::{Regex, RegexBuilder};
+
+use crate::{error::Result, feed::Post, util::parse_date};
+
+/// A compiled expression.
+#[derive(Clone, Debug)]
+pub struct Expr(Node);
+
+#[derive(Clone, Debug)]
+enum Node {
+  And(Box<Node>, Box<Node>),
+  Or(Box<Node>, Box<Node>),
+  Not(Box<Node>),
+  Text(TextField, TextCond),
+  Date(CmpOp, DateValue),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum TextField {
+  Title,
+  Body,
+  Link,
+  Author,
+  Guid,
+  Category,
+  Any,
+}
+
+#[derive(Clone, Debug)]
+enum TextCond {
+  Matches(Regex),
+  Equals(String),
+  In(Vec<String>),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum CmpOp {
+  Lt,
+  Le,
+  Gt,
+  Ge,
+  Eq,
+  Ne,
+}
+
+#[derive(Clone, Debug)]
+enum DateValue {
+  Absolute(DateTime<FixedOffset>),
+  // relative to the time of evaluation
+  Relative(chrono::Duration),
+}
+
+impl Expr {
+  pub fn parse(input: &str) -> Result<Self> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let node = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+      anyhow::bail!("unexpected {token} in expression: {input}");
+    }
+    Ok(Self(node))
+  }
+
+  pub fn matches(&self, post: &Post) -> bool {
+    self.0.eval(post)
+  }
+}
+
+impl Node {
+  fn eval(&self, post: &Post) -> bool {
+    match self {
+      Self::And(a, b) => a.eval(post) && b.eval(post),
+      Self::Or(a, b) => a.eval(post) || b.eval(post),
+      Self::Not(a) => !a.eval(post),
+      Self::Text(field, cond) => {
+        field.values(post).iter().any(|value| cond.holds(value))
+      }
+      Self::Date(op, value) => {
+        let Some(date) = post.pub_date() else {
+          return false;
+        };
+        let other = match value {
+          DateValue::Absolute(date) => *date,
+          DateValue::Relative(offset) => (Utc::now() + *offset).fixed_offset(),
+        };
+        op.holds(date.cmp(&other))
+      }
+    }
+  }
+}
+
+impl TextField {
+  fn from_name(name: &str) -> Option<Self> {
+    Some(match name {
+      "title" => Self::Title,
+      "body" => Self::Body,
+      "link" => Self::Link,
+      "author" => Self::Author,
+      "guid" => Self::Guid,
+      "category" | "categories" => Self::Category,
+      "any" => Self::Any,
+      _ => return None,
+    })
+  }
+
+  fn values<'a>(&self, post: &'a Post) -> Vec<&'a str> {
+    match self {
+      Self::Title => post.title().into_iter().collect(),
+      Self::Body => post.bodies(),
+      Self::Link => post.link().into_iter().collect(),
+      Self::Author => post.author().into_iter().collect(),
+      Self::Guid => post.guid().into_iter().collect(),
+      Self::Category => post.categories(),
+      Self::Any => post
+        .title()
+        .into_iter()
+        .chain(post.bodies())
+        .chain(post.categories())
+        .collect(),
+    }
+  }
+}
+
+impl TextCond {
+  fn holds(&self, value: &str) -> bool {
+    match self {
+      Self::Matches(regex) => regex.is_match(value),
+      Self::Equals(expected) => value.to_lowercase() == *expected,
+      Self::In(expected) => expected.contains(&value.to_lowercase()),
+    }
+  }
+}
+
+impl CmpOp {
+  fn holds(&self, ordering: std::cmp::Ordering) -> bool {
+    match self {
+      Self::Lt => ordering.is_lt(),
+      Self::Le => ordering.is_le(),
+      Self::Gt => ordering.is_gt(),
+      Self::Ge => ordering.is_ge(),
+      Self::Eq => ordering.is_eq(),
+      Self::Ne => ordering.is_ne(),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+  Word(String),
+  Quoted(String),
+  Op(&'static str),
+  LParen,
+  RParen,
+  LBracket,
+  RBracket,
+  Comma,
+}
+
+impl fmt::Display for Token {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Word(word) => write!(f, "'{word}'"),
+      Self::Quoted(s) => write!(f, "{s:?}"),
+      Self::Op(op) => write!(f, "'{op}'"),
+      Self::LParen => write!(f, "'('"),
+      Self::RParen => write!(f, "')'"),
+      Self::LBracket => write!(f, "'['"),
+      Self::RBracket => write!(f, "']'"),
+      Self::Comma => write!(f, "','"),
+    }
+  }
+}
+
+// longer operators first so "<=" isn't read as "<"
+const OPERATORS: [&str; 8] = ["!~", "==", "!=", "<=", ">=", "~", "<", ">"];
+
+fn is_word_char(c: char) -> bool {
+  c.is_alphanumeric() || "_-.:/+@".contains(c)
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+  let mut tokens = vec![];
+  let mut rest = input.trim_start();
+
+  while let Some(c) = rest.chars().next() {
+    let (token, len) = match c {
+      '(' => (Token::LParen, 1),
+      ')' => (Token::RParen, 1),
+      '[' => (Token::LBracket, 1),
+      ']' => (Token::RBracket, 1),
+      ',' => (Token::Comma, 1),
+      '"' | '\'' => {
+        let Some(end) = rest[1..].find(c) else {
+          anyhow::bail!("unterminated string in expression: {input}");
+        };
+        (Token::Quoted(rest[1..end + 1].to_owned()), end + 2)
+      }
+      c if is_word_char(c) => {
+        let len = rest.find(|c| !is_word_char(c)).unwrap_or(rest.len());
+        (Token::Word(rest[..len].to_owned()), len)
+      }
+      _ => match OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+        Some(op) => (Token::Op(op), op.len()),
+        None => anyhow::bail!("unexpected '{c}' in expression: {input}"),
+      },
+    };
+
+    tokens.push(token);
+    rest = rest[len..].trim_start();
+  }
+
+  Ok(tokens)
+}
+
+struct Parser {
+  tokens: Vec<Token>,
+  pos: usize,
+}
+
+impl Parser {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.pos)
+  }
+
+  fn next(&mut self) -> Result<Token> {
+    let token = self.tokens.get(self.pos).cloned();
+    self.pos += 1;
+    token.ok_or_else(|| anyhow::anyhow!("unexpected end of expression"))
+  }
+
+  fn eat_keyword(&mut self, keyword: &str) -> bool {
+    let found = matches!(self.peek(), Some(Token::Word(w)) if w == keyword);
+    if found {
+      self.pos += 1;
+    }
+    found
+  }
+
+  fn expect(&mut self, expected: Token) -> Result<()> {
+    match self.next()? {
+      token if token == expected => Ok(()),
+      token => anyhow::bail!("expected {expected}, found {token}"),
+    }
+  }
+
+  fn parse_or(&mut self) -> Result<Node> {
+    let mut node = self.parse_and()?;
+    while self.eat_keyword("or") {
+      node = Node::Or(Box::new(node), Box::new(self.parse_and()?));
+    }
+    Ok(node)
+  }
+
+  fn parse_and(&mut self) -> Result<Node> {
+    let mut node = self.parse_not()?;
+    while self.eat_keyword("and") {
+      node = Node::And(Box::new(node), Box::new(self.parse_not()?));
+    }
+    Ok(node)
+  }
+
+  fn parse_not(&mut self) -> Result<Node> {
+    if self.eat_keyword("not") {
+      return Ok(Node::Not(Box::new(self.parse_not()?)));
+    }
+
+    if self.peek() == Some(&Token::LParen) {
+      self.pos += 1;
+      let node = self.parse_or()?;
+      self.expect(Token::RParen)?;
+      return Ok(node);
+    }
+
+    self.parse_condition()
+  }
+
+  fn parse_condition(&mut self) -> Result<Node> {
+    let field = match self.next()? {
+      Token::Word(field) => field,
+      token => anyhow::bail!("expected a field, found {token}"),
+    };
+
+    if field == "date" {
+      return self.parse_date_condition();
+    }
+
+    let Some(text_field) = TextField::from_name(&field) else {
+      anyhow::bail!("unknown field '{field}'");
+    };
+
+    let (cond, negated) = match self.next()? {
+      Token::Op(op @ ("~" | "!~")) => {
+        let pattern = self.parse_value()?;
+        let regex =
+          RegexBuilder::new(&pattern).case_insensitive(true).build()?;
+        (TextCond::Matches(regex), op == "!~")
+      }
+      Token::Op(op @ ("==" | "!=")) => {
+        let value = self.parse_value()?.to_lowercase();
+        (TextCond::Equals(value), op == "!=")
+      }
+      Token::Word(w) if w == "in" => {
+        let values = self.parse_list()?;
+        let values = values.iter().map(|v| v.to_lowercase()).collect();
+        (TextCond::In(values), false)
+      }
+      token => {
+        anyhow::bail!("unsupported operator {token} for field '{field}'")
+      }
+    };
+
+    let node = Node::Text(text_field, cond);
+    Ok(if negated {
+      Node::Not(Box::new(node))
+    } else {
+      node
+    })
+  }
+
+  fn parse_date_condition(&mut self) -> Result<Node> {
+    let op = match self.next()? {
+      Token::Op("<") => CmpOp::Lt,
+      Token::Op("<=") => CmpOp::Le,
+      Token::Op(">") => CmpOp::Gt,
+      Token::Op(">=") => CmpOp::Ge,
+      Token::Op("==") => CmpOp::Eq,
+      Token::Op("!=") => CmpOp::Ne,
+      token => anyhow::bail!("unsupported operator {token} for field 'date'"),
+    };
+
+    let value = self.parse_value()?;
+    Ok(Node::Date(op, parse_date_value(&value)?))
+  }
+
+  fn parse_value(&mut self) -> Result<String> {
+    match self.next()? {
+      Token::Word(value) | Token::Quoted(value) => Ok(value),
+      token => anyhow::bail!("expected a value, found {token}"),
+    }
+  }
+
+  fn parse_list(&mut self) -> Result<Vec<String>> {
+    self.expect(Token::LBracket)?;
+    let mut values = vec![];
+    if self.peek() == Some(&Token::RBracket) {
+      self.pos += 1;
+      return Ok(values);
+    }
+
+    loop {
+      values.push(self.parse_value()?);
+      match self.next()? {
+        Token::Comma => continue,
+        Token::RBracket => return Ok(values),
+        token => anyhow::bail!("expected ',' or ']', found {token}"),
+      }
+    }
+  }
+}
+
+fn parse_date_value(value: &str) -> Result<DateValue> {
+  let (sign, duration) = match value.strip_prefix('-') {
+    Some(duration) => (-1, duration),
+    None => (1, value.strip_prefix('+').unwrap_or(value)),
+  };
+  if let Ok(duration) = duration_str::parse(duration) {
+    let duration = chrono::Duration::from_std(duration)?;
+    return Ok(DateValue::Relative(duration * sign));
+  }
+
+  if let Some(date) = parse_date(value) {
+    return Ok(DateValue::Absolute(date));
+  }
+  if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+    let date = date.and_time(Default::default()).and_utc();
+    return Ok(DateValue::Absolute(date.fixed_offset()));
+  }
+
+  anyhow::bail!("invalid date or duration '{value}'")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::feed::Feed;
+
+  const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0"><channel><title>Test</title><link>https://example.com</link>
+<description>Test</description>
+<item>
+  <title>Announcing Rust 2.0</title>
+  <author>alice@example.com (Alice)</author>
+  <category>Release</category>
+  <category>Rust</category>
+  <pubDate>Mon, 01 Jan 2024 10:00:00 +0000</pubDate>
+</item>
+<item>
+  <title>Weekly digest: rust and more</title>
+  <author>bot</author>
+  <category>News</category>
+  <pubDate>Mon, 01 Jan 2024 10:00:00 +0000</pubDate>
+</item>
+<item>
+  <title>From the future</title>
+  <author>Bob</author>
+  <pubDate>Tue, 01 Jan 2999 10:00:00 +0000</pubDate>
+</item>
+</channel></rss>"#;
+
+  fn matching(expr: &str) -> Vec<String> {
+    let expr = Expr::parse(expr).unwrap();
+    let mut feed = Feed::from_xml_content(FEED.as_bytes()).unwrap();
+    feed
+      .take_posts()
+      .iter()
+      .filter(|post| expr.matches(post))
+      .map(|post| post.title().unwrap().to_owned())
+      .collect()
+  }
+
+  #[test]
+  fn test_text_conditions() {
+    assert_eq!(
+      matching("title ~ rust"),
+      ["Announcing Rust 2.0", "Weekly digest: rust and more"]
+    );
+    assert_eq!(
+      matching(r#"title ~ "rust" and not author == "BOT""#),
+      ["Announcing Rust 2.0"]
+    );
+    assert_eq!(matching("title !~ '^weekly'").len(), 2);
+    assert_eq!(
+      matching("category in [news, release]"),
+      ["Announcing Rust 2.0", "Weekly digest: rust and more"]
+    );
+    assert_eq!(matching("category in []").len(), 0);
+  }
+
+  #[test]
+  fn test_date_conditions() {
+    assert_eq!(matching("date > -7d"), ["From the future"]);
+    assert_eq!(matching("date < 2024-06-01").len(), 2);
+    assert_eq!(
+      matching("date < 2024-06-01 and (author == bot or category == rust)")
+        .len(),
+      2
+    );
+  }
+
+  #[test]
+  fn test_precedence() {
+    // and binds tighter than or
+    assert_eq!(
+      matching("author == bob or title ~ digest and author == alice"),
+      ["From the future"]
+    );
+    assert_eq!(matching("not not author == bob"), ["From the future"]);
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    for expr in [
+      "",
+      "title",
+      "title ~",
+      "color == red",
+      "date ~ yesterday",
+      "date > yesterday",
+      "title ~ 'rust",
+      "(title ~ rust",
+      "title ~ rust and",
+      "title ~ rust author == bot",
+      "category in [a b]",
+      "title ~ '('",
+    ] {
+      assert!(Expr::parse(expr).is_err(), "{expr} should fail to parse");
+    }
+  }
+}
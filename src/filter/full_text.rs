@@ -1,4 +1,6 @@
-use std::sync::Arc;
+mod site_config;
+
+use std::path::PathBuf;
 use std::time::Duration;
 
 use futures::{StreamExt, stream};
@@ -8,11 +10,13 @@ use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::client::{self, Client};
-use crate::error::Result;
+use crate::error::{DisallowedByRobots, Result};
 use crate::feed::{Feed, Post};
 use crate::util::convert_relative_url;
 
-use super::html::{KeepElement, KeepElementConfig};
+use super::html::{
+  KeepElement, KeepElementConfig, RemoveElement, RemoveElementConfig,
+};
 use super::{FeedFilter, FeedFilterConfig, FilterContext};
 
 const DEFAULT_PARALLELISM: usize = 20;
@@ -33,6 +37,33 @@ pub struct FullTextConfig {
   keep_guid: Option<bool>,
   /// The client configuration
   client: Option<client::ClientConfig>,
+  /// Settings for the posts linking to specific sites. The first rule
+  /// matching the domain of the link is used.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  rules: Vec<SiteRuleConfig>,
+  /// A directory of FiveFilters site config files (e.g.
+  /// "example.com.txt") to import rules from. Only the body, strip,
+  /// strip_id_or_class and http_header directives are supported. The
+  /// rules above take precedence.
+  site_config_dir: Option<PathBuf>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(
+  JsonSchema, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash,
+)]
+pub struct SiteRuleConfig {
+  /// Glob patterns of the domains the rule applies to,
+  /// e.g. "*.example.com"
+  domains: Vec<String>,
+  /// Keep only content inside an element of the full text
+  keep_element: Option<KeepElementConfig>,
+  /// Remove these elements from the full text
+  remove_element: Option<RemoveElementConfig>,
+  /// Whether to simplify the HTML before saving it
+  simplify: Option<bool>,
+  /// The client configuration, on top of the one for the filter
+  client: Option<client::ClientConfig>,
 }
 
 pub struct FullTextFilter {
@@ -42,6 +73,15 @@ pub struct FullTextFilter {
   keep_element: Option<KeepElement>,
   simplify: bool,
   keep_guid: bool,
+  rules: Vec<SiteRule>,
+}
+
+struct SiteRule {
+  domains: Vec<String>,
+  keep_element: Option<KeepElement>,
+  remove_element: Option<RemoveElement>,
+  simplify: Option<bool>,
+  client: Option<Client>,
 }
 
 #[async_trait::async_trait]
@@ -53,6 +93,15 @@ impl FeedFilterConfig for FullTextConfig {
     let default_cache_ttl = Duration::from_secs(12 * 60 * 60);
     let conf_client = self.client.unwrap_or_default();
     let client = conf_client.build(default_cache_ttl)?;
+    let mut rule_configs = self.rules;
+    if let Some(dir) = self.site_config_dir {
+      let dir = crate::util::resolve_config_path(&dir);
+      rule_configs.extend(site_config::load_dir(&dir)?);
+    }
+    let mut rules = vec![];
+    for rule in rule_configs {
+      rules.push(rule.build(&conf_client, default_cache_ttl).await?);
+    }
     let parallelism = self.parallelism.unwrap_or(DEFAULT_PARALLELISM);
     let append_mode = self.append_mode.unwrap_or(false);
     let simplify = self.simplify.unwrap_or(false);
@@ -69,14 +118,55 @@ impl FeedFilterConfig for FullTextConfig {
       keep_element,
       simplify,
       keep_guid,
+      rules,
+    })
+  }
+}
+
+impl SiteRuleConfig {
+  async fn build(
+    self,
+    filter_client: &client::ClientConfig,
+    default_cache_ttl: Duration,
+  ) -> Result<SiteRule> {
+    let client = match self.client {
+      Some(client) => Some(
+        client
+          .with_defaults(filter_client)
+          .build(default_cache_ttl)?,
+      ),
+      None => None,
+    };
+    let keep_element = match self.keep_element {
+      Some(c) => Some(c.build().await?),
+      None => None,
+    };
+    let remove_element = match self.remove_element {
+      Some(c) => Some(c.build().await?),
+      None => None,
+    };
+
+    Ok(SiteRule {
+      domains: self.domains,
+      keep_element,
+      remove_element,
+      simplify: self.simplify,
+      client,
     })
   }
 }
 
 impl FullTextFilter {
-  async fn fetch_html(&self, url: &str) -> Result<String> {
-    let url = Url::parse(url)?;
-    let resp = self.client.get(&url).await?;
+  fn rule_for(&self, url: &Url) -> Option<&SiteRule> {
+    let domain = url.domain()?;
+    self
+      .rules
+      .iter()
+      .find(|rule| crate::util::domain_matches(&rule.domains, domain))
+  }
+
+  async fn fetch_html(&self, client: &Client, url: &Url) -> Result<String> {
+    let resp = client.get(url).await?;
     let content_type = resp.content_type().unwrap_or(mime::TEXT_HTML);
 
     if content_type.essence_str() != "text/html" {
@@ -91,14 +181,20 @@ impl FullTextFilter {
 
   async fn try_fetch_full_post(&self, post: &mut Post) -> Result<()> {
     let link = post.link_or_err()?.to_owned();
-    let text = self.fetch_html(&link).await?;
+    let url = Url::parse(&link)?;
+    let rule = self.rule_for(&url);
+    let client = rule.and_then(|r| r.client.as_ref()).unwrap_or(&self.client);
+    let text = self.fetch_html(client, &url).await?;
 
     // Optimization: the strip_post_content can be CPU intensive. Spawn the blocking
     // task on a different CPU to improve parallelism.
-    let simplify = self.simplify;
-    let keep_element = Arc::new(self.keep_element.clone());
+    let simplify = rule.and_then(|r| r.simplify).unwrap_or(self.simplify);
+    let keep_element = rule
+      .and_then(|r| r.keep_element.clone())
+      .or_else(|| self.keep_element.clone());
+    let remove_element = rule.and_then(|r| r.remove_element.clone());
     let text = tokio::task::spawn_blocking(move || {
-      strip_post_content(text, &link, simplify, keep_element)
+      strip_post_content(text, &link, simplify, keep_element, remove_element)
     })
     .await?;
 
@@ -128,6 +224,8 @@ impl FullTextFilter {
     // completely.
     match self.try_fetch_full_post(&mut post).await {
       Ok(()) => Ok(post),
+      // not an error, the post is just left as is
+      Err(e) if e.is::<DisallowedByRobots>() => Ok(post),
       Err(e) => {
         let message = format!("\n<br>\n<br>\nerror fetching full text: {e}");
         post.modify_bodies(|body| {
@@ -171,7 +269,8 @@ fn strip_post_content(
   html: String,
   link: &str,
   simplify: bool,
-  keep_element: Arc<Option<KeepElement>>,
+  keep_element: Option<KeepElement>,
+  remove_element: Option<RemoveElement>,
 ) -> String {
   let mut html = scraper::Html::parse_document(&html);
   convert_relative_url(&mut html, link);
@@ -183,9 +282,13 @@ fn strip_post_content(
     text = crate::util::html_body(&text);
   }
 
-  if let Some(k) = keep_element.as_ref() {
+  if let Some(k) = keep_element {
     k.filter_body(&mut text);
   }
 
+  if let Some(r) = remove_element {
+    r.filter_body(&mut text);
+  }
+
   text
 }
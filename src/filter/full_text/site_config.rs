@@ -0,0 +1,360 @@
+//! Import of [FiveFilters site config
+//! files](https://help.fivefilters.org/full-text-rss/site-patterns.html).
+//!
+//! A file named `example.com.txt` applies to example.com and
+//! www.example.com, and one named `.example.com.txt` to all its
+//! subdomains. Only the directives with an equivalent in the full
+//! text rules are read: `body`, `strip`, `strip_id_or_class` and
+//! `http_header(...)`. The XPath expressions are converted to CSS
+//! selectors, which covers the common forms like
+//! `//div[@id='content']` or `//*[contains(@class, 'ads')]`. Others
+//! are skipped with a warning.
+
+use std::{collections::BTreeMap, path::Path, sync::LazyLock};
+
+use regex::Regex;
+use tracing::warn;
+
+use super::SiteRuleConfig;
+use crate::{
+  client::ClientConfig,
+  error::Result,
+  filter::html::{KeepElementConfig, RemoveElementConfig},
+};
+
+/// Read the rules from all the site config files in the directory.
+/// Rules for exact domains come before the ones for subdomains.
+pub fn load_dir(dir: &Path) -> Result<Vec<SiteRuleConfig>> {
+  let mut files = vec![];
+  for entry in std::fs::read_dir(dir)? {
+    let path = entry?.path();
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+      continue;
+    };
+    if let Some(domain) = name.strip_suffix(".txt") {
+      files.push((domain.starts_with('.'), domain.to_owned(), path.clone()));
+    }
+  }
+  files.sort();
+
+  let mut rules = vec![];
+  for (_, domain, path) in files {
+    let content = match std::fs::read_to_string(&path) {
+      Ok(content) => content,
+      Err(e) => {
+        warn!("skipping unreadable site config {}: {e}", path.display());
+        continue;
+      }
+    };
+    rules.extend(parse(&domain, &content));
+  }
+  Ok(rules)
+}
+
+/// The rule from the site config file for the domain, or None if the
+/// file has no supported directives.
+pub fn parse(domain: &str, content: &str) -> Option<SiteRuleConfig> {
+  let mut body = vec![];
+  let mut strip = vec![];
+  let mut headers = BTreeMap::new();
+
+  for line in content.lines() {
+    let line = line.trim();
+    if line.starts_with('#') {
+      continue;
+    }
+    let Some((directive, value)) = line.split_once(':') else {
+      continue;
+    };
+    let value = value.trim();
+
+    match directive.trim() {
+      "body" => body.extend(convert(domain, value)),
+      "strip" => strip.extend(convert(domain, value)),
+      "strip_id_or_class" => {
+        let name = css_string(value.trim_matches(['"', '\'']));
+        let css = format!("[id*={name}], [class*={name}]");
+        strip.extend(checked(domain, value, css));
+      }
+      directive => {
+        if let Some(name) = directive
+          .strip_prefix("http_header(")
+          .and_then(|d| d.strip_suffix(')'))
+        {
+          headers.insert(name.trim().to_ascii_lowercase(), value.to_owned());
+        }
+      }
+    }
+  }
+
+  let client = (!headers.is_empty()).then(|| ClientConfig {
+    user_agent: headers.remove("user-agent"),
+    cookie: headers.remove("cookie"),
+    referer: headers.remove("referer"),
+    headers,
+    ..Default::default()
+  });
+
+  if body.is_empty() && strip.is_empty() && client.is_none() {
+    return None;
+  }
+
+  let domains = match domain.strip_prefix('.') {
+    Some(domain) => vec![domain.to_owned(), format!("*.{domain}")],
+    None => vec![domain.to_owned(), format!("www.{domain}")],
+  };
+
+  Some(SiteRuleConfig {
+    domains,
+    keep_element: (!body.is_empty())
+      .then(|| KeepElementConfig::new(body.join(", "))),
+    remove_element: (!strip.is_empty())
+      .then(|| RemoveElementConfig::new(strip)),
+    simplify: None,
+    client,
+  })
+}
+
+fn convert(domain: &str, xpath: &str) -> Option<String> {
+  let Some(css) = xpath_to_css(xpath) else {
+    warn!("skipping unsupported XPath in site config for {domain}: {xpath}");
+    return None;
+  };
+  checked(domain, xpath, css)
+}
+
+// the conversion doesn't validate element and attribute names, so the
+// result may still fail to parse, e.g. with a name starting with a digit
+fn checked(domain: &str, value: &str, css: String) -> Option<String> {
+  if scraper::Selector::parse(&css).is_err() {
+    warn!("skipping invalid selector in site config for {domain}: {value}");
+    return None;
+  }
+  Some(css)
+}
+
+/// Convert the XPath to an equivalent CSS selector.
+pub fn xpath_to_css(xpath: &str) -> Option<String> {
+  let alternatives = split_top_level(xpath, '|')
+    .into_iter()
+    .map(|path| path_to_css(path.trim()))
+    .collect::<Option<Vec<_>>>()?;
+  Some(alternatives.join(", "))
+}
+
+fn path_to_css(path: &str) -> Option<String> {
+  let mut css = String::new();
+  let mut rest = path;
+
+  while !rest.is_empty() {
+    let combinator = if let Some(r) = rest.strip_prefix("//") {
+      rest = r;
+      " "
+    } else if let Some(r) = rest.strip_prefix('/') {
+      rest = r;
+      " > "
+    } else {
+      return None;
+    };
+
+    let end = split_top_level(rest, '/')[0].len();
+    let step = step_to_css(&rest[..end])?;
+    if !css.is_empty() {
+      css.push_str(combinator);
+    }
+    css.push_str(&step);
+    rest = &rest[end..];
+  }
+
+  (!css.is_empty()).then_some(css)
+}
+
+// an element name followed by predicates, e.g. div[@id='content']
+fn step_to_css(step: &str) -> Option<String> {
+  let (name, mut predicates) = match step.find('[') {
+    Some(pos) => (&step[..pos], &step[pos..]),
+    None => (step, ""),
+  };
+  if name != "*" && !name.chars().all(|c| c.is_alphanumeric() || c == '-') {
+    return None;
+  }
+
+  let mut css = name.to_owned();
+  while let Some(rest) = predicates.strip_prefix('[') {
+    let end = split_top_level(rest, ']')[0].len();
+    let predicate = &rest[..end];
+    for condition in predicate.split(" and ") {
+      css.push_str(&predicate_to_css(condition.trim())?);
+    }
+    predicates = rest.get(end + 1..)?;
+  }
+
+  if !predicates.is_empty() {
+    return None;
+  }
+  Some(css)
+}
+
+fn predicate_to_css(predicate: &str) -> Option<String> {
+  // contains(concat(' ', normalize-space(@class), ' '), ' name ')
+  static CLASS_TOKEN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+      r#"^contains\(\s*concat\(\s*['"] ['"]\s*,"#,
+      r#"\s*normalize-space\(\s*@class\s*\)\s*,\s*['"] ['"]\s*\)\s*,"#,
+      r#"\s*['"]\s*([^'"\s]+)\s*['"]\s*\)$"#,
+    ))
+    .unwrap()
+  });
+  static FUNCTION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+      r#"^(contains|starts-with)\(\s*@([\w-]+)\s*,\s*['"]([^'"]*)['"]\s*\)$"#,
+    )
+    .unwrap()
+  });
+  static EQUALS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^@([\w-]+)\s*=\s*['"]([^'"]*)['"]$"#).unwrap()
+  });
+  static HAS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^@([\w-]+)$").unwrap());
+
+  if let Ok(n) = predicate.parse::<usize>() {
+    return Some(format!(":nth-of-type({n})"));
+  }
+  if let Some(c) = CLASS_TOKEN.captures(predicate) {
+    return Some(format!("[class~={}]", css_string(&c[1])));
+  }
+  if let Some(c) = FUNCTION.captures(predicate) {
+    let op = if &c[1] == "contains" { "*=" } else { "^=" };
+    return Some(format!("[{}{op}{}]", &c[2], css_string(&c[3])));
+  }
+  if let Some(c) = EQUALS.captures(predicate) {
+    return Some(format!("[{}={}]", &c[1], css_string(&c[2])));
+  }
+  if let Some(c) = HAS.captures(predicate) {
+    return Some(format!("[{}]", &c[1]));
+  }
+
+  None
+}
+
+// a quoted CSS string
+fn css_string(value: &str) -> String {
+  let escaped = value.replace('\\', r"\\").replace('"', r#"\""#);
+  format!(r#""{escaped}""#)
+}
+
+// split on the separator, except inside brackets or quotes
+fn split_top_level(s: &str, sep: char) -> Vec<&str> {
+  let mut parts = vec![];
+  let mut depth = 0;
+  let mut quote = None;
+  let mut start = 0;
+
+  for (i, c) in s.char_indices() {
+    match (quote, c) {
+      (Some(q), c) if c == q => quote = None,
+      (Some(_), _) => {}
+      (None, '\'' | '"') => quote = Some(c),
+      (None, c) if c == sep && depth == 0 => {
+        parts.push(&s[start..i]);
+        start = i + c.len_utf8();
+      }
+      (None, '[' | '(') => depth += 1,
+      (None, ']' | ')') => depth -= 1,
+      _ => {}
+    }
+  }
+
+  parts.push(&s[start..]);
+  parts
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_xpath_to_css() {
+    let cases = [
+      ("//article", "article"),
+      ("//div[@id='content']", r#"div[id="content"]"#),
+      ("//*[@class=\"post\"]//p", r#"*[class="post"] p"#),
+      ("//div[contains(@class, 'ads')]", r#"div[class*="ads"]"#),
+      (
+        "//div[contains(concat(' ',normalize-space(@class),' '),' entry ')]",
+        r#"div[class~="entry"]"#,
+      ),
+      ("//main/div[2]", "main > div:nth-of-type(2)"),
+      ("//h1 | //div[@data-body]", "h1, div[data-body]"),
+      (
+        "//a[starts-with(@href, '/tag/') and @rel='tag']",
+        r#"a[href^="/tag/"][rel="tag"]"#,
+      ),
+    ];
+    for (xpath, css) in cases {
+      assert_eq!(xpath_to_css(xpath).as_deref(), Some(css), "{xpath}");
+    }
+
+    for xpath in [
+      "div",
+      "//div[last()]",
+      "//div/text()",
+      "//div[@id='a'",
+      "//div[@xml:lang='en']",
+    ] {
+      assert_eq!(xpath_to_css(xpath), None, "{xpath}");
+    }
+  }
+
+  #[test]
+  fn test_parse_site_config() {
+    let content = r"
+# comment
+body: //div[@id='article-body']
+strip: //aside | //div[@class='share']
+strip: //div[last()]
+strip_id_or_class: newsletter
+http_header(User-Agent): Mozilla/5.0
+http_header(x-token): abc
+prune: no
+test_url: https://example.com/article
+";
+    let rule = parse("example.com", content).unwrap();
+    let yaml = serde_yaml::to_string(&rule).unwrap();
+    let expected = r#"domains:
+- example.com
+- www.example.com
+keep_element: div[id="article-body"]
+remove_element:
+- aside, div[class="share"]
+- '[id*="newsletter"], [class*="newsletter"]'
+client:
+  user_agent: Mozilla/5.0
+  headers:
+    x-token: abc
+"#;
+    assert_eq!(yaml, expected);
+
+    let rule = parse(".example.org", "body: //article").unwrap();
+    assert_eq!(rule.domains, ["example.org", "*.example.org"]);
+    assert!(parse("example.net", "prune: no").is_none());
+  }
+
+  #[test]
+  fn test_parse_invalid_selectors() {
+    let content = r#"
+body: //1div
+strip: //div[@id='a\']
+strip_id_or_class: "a"b\"
+"#;
+    let rule = parse("example.com", content).unwrap();
+    assert!(rule.keep_element.is_none());
+    let expected = [r#"div[id="a\\"]"#, r#"[id*="a\"b\\"], [class*="a\"b\\"]"#];
+    assert_eq!(
+      rule.remove_element,
+      Some(RemoveElementConfig::new(
+        expected.map(String::from).to_vec()
+      ))
+    );
+  }
+}
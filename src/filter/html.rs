@@ -33,12 +33,19 @@ pub struct RemoveElementConfig {
   selectors: Vec<String>,
 }
 
+#[derive(Clone)]
 pub struct RemoveElement {
   selectors: Vec<Selector>,
 }
 
+impl RemoveElementConfig {
+  pub(crate) fn new(selectors: Vec<String>) -> Self {
+    Self { selectors }
+  }
+}
+
 // can't define FromStr for Selector due to Rust's orphan rule
-fn parse_selector(selector: &str) -> Result<Selector> {
+pub(crate) fn parse_selector(selector: &str) -> Result<Selector> {
   Selector::parse(selector)
     .map_err(|e| anyhow::anyhow!("bad selector: {selector}: {e}"))
 }
@@ -60,7 +67,7 @@ impl FeedFilterConfig for RemoveElementConfig {
 }
 
 impl RemoveElement {
-  fn filter_body(&self, body: &mut String) {
+  pub fn filter_body(&self, body: &mut String) {
     let mut html = Html::parse_fragment(body);
     let mut selected_node_ids = vec![];
     for selector in &self.selectors {
@@ -129,6 +136,12 @@ pub struct KeepElement {
   selectors: Vec<Selector>,
 }
 
+impl KeepElementConfig {
+  pub(crate) fn new(selector: String) -> Self {
+    Self { selector }
+  }
+}
+
 #[async_trait::async_trait]
 impl FeedFilterConfig for KeepElementConfig {
   type Filter = KeepElement;
@@ -246,6 +259,14 @@ impl FeedFilterConfig for SplitConfig {
   type Filter = Split;
 
   async fn build(self) -> Result<Self::Filter> {
+    self.compile()
+  }
+}
+
+impl SplitConfig {
+  /// Parse the selectors, for when the split is used outside of a
+  /// filter pipeline.
+  pub fn compile(&self) -> Result<Split> {
     let parse_selector_opt = |s: &Option<String>| -> Result<Option<Selector>> {
       match s {
         Some(s) => Ok(Some(parse_selector(s)?)),
@@ -387,7 +408,7 @@ impl Split {
     template.set_guid(link);
   }
 
-  fn split(&self, mut post: Post) -> Result<Vec<Post>> {
+  pub(crate) fn split(&self, mut post: Post) -> Result<Vec<Post>> {
     let mut posts = vec![];
 
     let Some(body) = post.first_body() else {
@@ -179,7 +179,6 @@ fn rewrite_html(
   config: &Config,
   html: &str,
 ) -> Option<String> {
-  use glob_match::glob_match;
   use lol_html::{RewriteStrSettings, element};
 
   let selector = config.selector();
@@ -187,7 +186,7 @@ fn rewrite_html(
     (None, _) => true,
     (_, None) => false,
     (Some(domains), Some(domain)) => {
-      domains.iter().any(|pat| glob_match(pat, domain))
+      crate::util::domain_matches(domains, domain)
     }
   };
 
@@ -13,7 +13,7 @@ use crate::client::{Client, ClientConfig};
 use crate::error::{DynamicSourceUnspecified, Result};
 use crate::feed::{Feed, FeedFormat, Post};
 use crate::filter::{FeedFilter, FeedFilterConfig, FilterContext};
-use crate::source::Source;
+use crate::source::{CommandSource, NextPage, Pagination, Source, page_url};
 use crate::util;
 
 const DEFAULT_CACHE_TTL_SECS: u64 = 5 * 60;
@@ -36,6 +36,14 @@ pub struct JsonToFeedConfig {
   /// Optional HTTP client configuration for fetching the JSON source.
   #[serde(default)]
   pub client: Option<ClientConfig>,
+  /// Read the JSON from the stdout of a command instead of fetching
+  /// a URL. Can't be used together with `url`.
+  #[serde(default)]
+  pub exec: Option<CommandSource>,
+  /// Fetch the following pages and merge their items. Use `${page}`
+  /// in `url` for paginating by page number.
+  #[serde(default)]
+  pub pagination: Option<Pagination>,
 }
 
 #[derive(
@@ -95,6 +103,10 @@ pub struct JsonToFeedFilter {
   item_map: ParsedFieldMap,
   feed_meta_map: ParsedFeedMetaMap,
   client: Client,
+  command: Option<CommandSource>,
+  url: Option<String>,
+  pagination: Option<Pagination>,
+  next_page_path: Option<CompiledJsonPath>,
 }
 
 #[async_trait::async_trait]
@@ -108,9 +120,30 @@ impl FeedFilterConfig for JsonToFeedConfig {
       map,
       feed,
       client,
+      exec,
+      pagination,
     } = self;
 
-    let source = match url.map(|u| Url::parse(&u)).transpose()? {
+    if let Some(command) = &exec {
+      if url.is_some() || pagination.is_some() {
+        anyhow::bail!("`exec` can't be used with `url` or `pagination`");
+      }
+      command.validate()?;
+    }
+
+    if let Some(pagination) = &pagination {
+      let url = url.as_deref().unwrap_or_default();
+      pagination.validate(url, &["next_json_path", "page"])?;
+    }
+    let next_page_path = match pagination.as_ref().map(Pagination::next_page) {
+      Some(NextPage::JsonPath(path)) => Some(
+        CompiledJsonPath::compile(path).map_err(|e| anyhow::anyhow!("{e}"))?,
+      ),
+      _ => None,
+    };
+
+    let first_page = url.as_deref().map(|u| Url::parse(&page_url(u, 1)));
+    let source = match first_page.transpose()? {
       Some(url) => Source::AbsoluteUrl(url),
       None => Source::Dynamic,
     };
@@ -129,22 +162,68 @@ impl FeedFilterConfig for JsonToFeedConfig {
       item_map,
       feed_meta_map,
       client,
+      command: exec,
+      url,
+      pagination,
+      next_page_path,
     })
   }
 }
 
 #[async_trait::async_trait]
 impl FeedFilter for JsonToFeedFilter {
-  async fn run(&self, ctx: &mut FilterContext, mut feed: Feed) -> Result<Feed> {
-    let url = self.source.full_url(ctx).ok_or(DynamicSourceUnspecified)?;
-    let response = self.client.get(&url).await?.error_for_status()?;
-    let body = response.text()?;
-    let root: Value = serde_json::from_str(&body)?;
+  async fn run(&self, ctx: &mut FilterContext, feed: Feed) -> Result<Feed> {
+    if let Some(command) = &self.command {
+      let root = serde_json::from_slice(&command.run().await?)?;
+      return self.build_feed(&root, feed);
+    }
 
-    let feed_meta = self.feed_meta_map.select(&root)?;
+    let ctx = &*ctx;
+    let feed = &feed;
+    let url_of_page = |page| -> Result<Url> {
+      Ok(match &self.url {
+        Some(url) => Url::parse(&page_url(url, page))?,
+        None => self.source.full_url(ctx).ok_or(DynamicSourceUnspecified)?,
+      })
+    };
+    let fetch_page = |page, next: Option<Url>| async move {
+      // pages linked from the previous one are always fetched with GET
+      let (url, response) = match next {
+        Some(url) => {
+          let response = self.client.get(&url).await?;
+          (url, response)
+        }
+        None => {
+          let url = url_of_page(page)?;
+          let response = self.client.get_source(&url).await?;
+          (url, response)
+        }
+      };
+      let response = response.error_for_status()?;
+      let root: Value = serde_json::from_str(&response.text()?)?;
+      let next = self.select_next_page(&root, &url);
+      Ok((self.build_feed(&root, feed.clone())?, next))
+    };
+
+    match &self.pagination {
+      Some(pagination) => pagination.collect(url_of_page(1)?, fetch_page).await,
+      None => Ok(fetch_page(1, None).await?.0),
+    }
+  }
+}
+
+impl JsonToFeedFilter {
+  fn select_next_page(&self, root: &Value, url: &Url) -> Option<Url> {
+    let next_page_path = self.next_page_path.as_ref()?;
+    let next = next_page_path.select(root).ok()?;
+    url.join(next.first()?.as_str()?).ok()
+  }
+
+  fn build_feed(&self, root: &Value, mut feed: Feed) -> Result<Feed> {
+    let feed_meta = self.feed_meta_map.select(root)?;
     feed_meta.apply(&mut feed)?;
 
-    let items = self.items_path.select(&root)?;
+    let items = self.items_path.select(root)?;
     let mut posts = Vec::with_capacity(items.len());
 
     for item in items {
@@ -152,6 +231,7 @@ impl FeedFilter for JsonToFeedFilter {
       let post = match &feed {
         Feed::Rss(_) => fields.build_rss_post()?,
         Feed::Atom(_) => fields.build_atom_post()?,
+        Feed::Json(_) => fields.build_json_post()?,
       };
 
       posts.push(post);
@@ -392,6 +472,12 @@ impl FieldValues<'_> {
       .build_rss_post()
       .map(|post| post.into_format(FeedFormat::Atom))
   }
+
+  fn build_json_post(&self) -> Result<Post> {
+    self
+      .build_rss_post()
+      .map(|post| post.into_format(FeedFormat::Json))
+  }
 }
 
 impl ConfigFeedMetaMap {
@@ -439,6 +525,7 @@ impl FeedMetaValues<'_> {
     match feed {
       Feed::Rss(channel) => channel.title = title,
       Feed::Atom(atom) => atom.title = atom_syndication::Text::plain(title),
+      Feed::Json(json) => json.title = title,
     }
 
     let link = get_field!(required; self.link);
@@ -454,6 +541,7 @@ impl FeedMetaValues<'_> {
           });
         }
       }
+      Feed::Json(json) => json.home_page_url = Some(link),
     }
 
     if let Some(description) = get_field!(optional; self.description) {
@@ -464,6 +552,9 @@ impl FeedMetaValues<'_> {
         Feed::Atom(atom) => {
           atom.subtitle = Some(atom_syndication::Text::plain(description));
         }
+        Feed::Json(json) => {
+          json.description = Some(description);
+        }
       }
     }
 
@@ -578,4 +669,63 @@ map:
       Some("audio/mpeg")
     );
   }
+
+  #[tokio::test]
+  async fn reads_json_from_command() {
+    let yaml = r#"
+exec:
+  command: ["cat", "fixtures/json/news.json"]
+items: "$.items[*]"
+feed:
+  title: "$.meta.title"
+  link: "$.meta.home"
+map:
+  title: "$.title"
+  link: "$.url"
+"#;
+
+    let config: JsonToFeedConfig = serde_yaml::from_str(yaml).unwrap();
+    let filter = config.build().await.unwrap();
+
+    let feed = Feed::from(&FromScratch {
+      format: FeedFormat::Rss,
+      title: "JSON feed".into(),
+      link: None,
+      description: None,
+    });
+
+    let mut ctx = FilterContext::new();
+    let feed = filter.run(&mut ctx, feed).await.unwrap();
+    assert_eq!(feed.post_count(), 2);
+  }
+
+  #[tokio::test]
+  async fn follows_next_page_json_path() {
+    let yaml = r#"
+url: "fixture:///json/page1.json?content_type=application/json"
+items: "$.items[*]"
+feed:
+  title: "$.title"
+  link: "https://example.com"
+map:
+  title: "$.title"
+  link: "$.url"
+pagination:
+  next_json_path: "$.next"
+"#;
+
+    let config: JsonToFeedConfig = serde_yaml::from_str(yaml).unwrap();
+    let filter = config.build().await.unwrap();
+
+    let feed = Feed::from(&FromScratch {
+      format: FeedFormat::Rss,
+      title: "JSON feed".into(),
+      link: None,
+      description: None,
+    });
+
+    let mut ctx = FilterContext::new();
+    let feed = filter.run(&mut ctx, feed).await.unwrap();
+    assert_eq!(feed.post_count(), 3);
+  }
 }
@@ -6,7 +6,7 @@ use tracing::warn;
 
 use crate::{
   error::Result,
-  feed::{Feed, Post},
+  feed::{Feed, JsonAttachment, Post},
 };
 
 use super::{FeedFilter, FeedFilterConfig, FilterContext};
@@ -15,8 +15,8 @@ use super::{FeedFilter, FeedFilterConfig, FilterContext};
   JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
 )]
 /// Find magnet link discovered in the body of entries and save it in
-/// the enclosure (RSS)/link (Atom). The resulting feed can be used in
-/// a torrent client.
+/// the enclosure (RSS)/link (Atom)/attachment (JSON). The resulting
+/// feed can be used in a torrent client.
 pub struct MagnetConfig {
   /// Match any `[a-fA-F0-9]{40}` as the info hash.
   #[serde(default)]
@@ -93,6 +93,12 @@ fn existing_magnet_link(post: &Post) -> Option<&str> {
       .filter(|l| l.href().starts_with("magnet:"))
       .map(atom_syndication::Link::href)
       .next(),
+    Post::Json(p) => p
+      .attachments
+      .iter()
+      .filter(|a| a.mime_type == "application/x-bittorrent")
+      .map(|a| a.url.as_str())
+      .next(),
   }
 }
 
@@ -118,6 +124,14 @@ fn set_magnet_link(post: &mut Post, link: String, override_: bool) {
       };
       p.links.push(link);
     }
+    Post::Json(p) => {
+      let attachment = JsonAttachment {
+        url: link,
+        mime_type: "application/x-bittorrent".to_string(),
+        ..Default::default()
+      };
+      p.attachments.push(attachment);
+    }
   }
 }
 
@@ -8,7 +8,7 @@ use std::borrow::Cow;
 
 use crate::{error::Result, feed::Feed, util::SingleOrVec};
 
-use super::{FeedFilter, FeedFilterConfig, FilterContext};
+use super::{FeedFilter, FeedFilterConfig, FilterContext, expr::Expr};
 
 #[derive(
   JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
@@ -33,10 +33,23 @@ enum AnyMatchConfig {
   SingleContains(String),
   /// Matches posts containing any of the given strings
   MultipleContains(Vec<String>),
+  /// Matches posts satisfying the expression. Must come before
+  /// MatchConfig, which accepts any map as all its fields are
+  /// optional.
+  Expr(ExprConfig),
   /// Full match configuration
   MatchConfig(MatchConfig),
 }
 
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+struct ExprConfig {
+  /// The expression to evaluate on each post, e.g.
+  /// `title ~ "rust" and not author == "bot" and date > -7d`
+  expr: String,
+}
+
 #[derive(
   JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, Default,
 )]
@@ -59,8 +72,8 @@ struct MatchConfig {
 }
 
 impl AnyMatchConfig {
-  fn into_match_config(self) -> MatchConfig {
-    match self {
+  fn into_select(self, action: Action) -> Result<Select> {
+    let match_config = match self {
       Self::SingleContains(s) => MatchConfig {
         contains: SingleOrVec::Vec(vec![s]),
         ..Default::default()
@@ -69,8 +82,16 @@ impl AnyMatchConfig {
         contains: SingleOrVec::Vec(v),
         ..Default::default()
       },
+      Self::Expr(ExprConfig { expr }) => {
+        return Ok(Select {
+          predicate: Predicate::Expr(Expr::parse(&expr)?),
+          action,
+        });
+      }
       Self::MatchConfig(m) => m,
-    }
+    };
+
+    match_config.into_select(action)
   }
 }
 
@@ -101,8 +122,7 @@ impl MatchConfig {
     let field = self.field;
 
     Ok(Select {
-      needle,
-      field,
+      predicate: Predicate::Match { needle, field },
       action,
     })
   }
@@ -151,7 +171,7 @@ impl FeedFilterConfig for KeepOnlyConfig {
   type Filter = Select;
 
   async fn build(self) -> Result<Self::Filter> {
-    self.0.into_match_config().into_select(Action::Include)
+    self.0.into_select(Action::Include)
   }
 }
 
@@ -160,25 +180,36 @@ impl FeedFilterConfig for DiscardConfig {
   type Filter = Select;
 
   async fn build(self) -> Result<Self::Filter> {
-    self.0.into_match_config().into_select(Action::Exclude)
+    self.0.into_select(Action::Exclude)
   }
 }
 
 #[derive(Clone, Debug)]
 pub struct Select {
-  needle: RegexSet,
-  field: Field,
+  predicate: Predicate,
   action: Action,
 }
 
-impl Select {
-  fn matches(&self, haystack: &[&str]) -> bool {
-    haystack.iter().any(|text| self.needle.is_match(text))
+#[derive(Clone, Debug)]
+enum Predicate {
+  Match { needle: RegexSet, field: Field },
+  Expr(Expr),
+}
+
+impl Predicate {
+  fn matches(&self, post: &crate::feed::Post) -> bool {
+    match self {
+      Self::Match { needle, field } => {
+        field.extract(post).iter().any(|text| needle.is_match(text))
+      }
+      Self::Expr(expr) => expr.matches(post),
+    }
   }
+}
 
+impl Select {
   fn should_keep(&self, post: &crate::feed::Post) -> bool {
-    let haystack = self.field.extract(post);
-    let matches = self.matches(&haystack);
+    let matches = self.predicate.matches(post);
 
     match self.action {
       Action::Include => matches,
@@ -301,6 +332,40 @@ mod test {
     assert_eq!(posts[0].title().unwrap(), "This Crystal Is ELECTRIC");
   }
 
+  #[test]
+  fn test_config_discard_expr() {
+    let config = r#"
+      discard:
+        expr: 'author == "bot" or not date > -30d'
+    "#;
+
+    let expected = DiscardConfig(AnyMatchConfig::Expr(ExprConfig {
+      expr: r#"author == "bot" or not date > -30d"#.into(),
+    }));
+
+    assert_filter_parse(config, expected);
+  }
+
+  #[tokio::test]
+  async fn test_keep_only_expr_filter() {
+    let config = r#"
+      !endpoint
+      path: /feed.xml
+      source: fixture:///duplicates.xml
+      filters:
+        - keep_only:
+            expr: 'title ~ "^another" and not link ~ mirror'
+    "#;
+
+    let mut feed = fetch_endpoint(config, "").await;
+    let titles: Vec<_> = feed
+      .take_posts()
+      .iter()
+      .map(|post| post.title().unwrap().to_owned())
+      .collect();
+    assert_eq!(titles, ["Another story", "Another story (updated)"]);
+  }
+
   #[tokio::test]
   async fn test_keep_only_case_sensitive_filter() {
     let config = r"
@@ -0,0 +1,335 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, FixedOffset};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  error::Result,
+  feed::{Feed, Post},
+};
+
+use super::{FeedFilter, FeedFilterConfig, FilterContext};
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+#[serde(untagged)]
+pub enum SortConfig {
+  /// Sort by the key in its default order
+  Simple(SortKey),
+  /// Sort by the key in the given order, with a tie-breaker
+  Full(SortSpec),
+}
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+pub struct SortSpec {
+  /// The key to sort by
+  by: SortKey,
+  /// The sort order (Default: desc for date, asc for the others)
+  #[serde(default)]
+  order: Option<Order>,
+  /// Sort posts with equal keys by this
+  #[serde(default)]
+  then: Option<Box<SortSpec>>,
+}
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+#[serde(untagged)]
+pub enum SortKey {
+  /// A field of the post
+  Field(SortField),
+  /// A part of a field captured by a regex
+  Capture(CaptureKey),
+}
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+  /// The publication date
+  Date,
+  /// The title, ignoring case
+  Title,
+  /// The author, ignoring case
+  Author,
+  /// The first category, ignoring case
+  Category,
+}
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+pub struct CaptureKey {
+  /// The field to match the regex against
+  field: CaptureField,
+  /// The regex to match. The first capture group is used as the key,
+  /// or the whole match if there is none. Keys that are numbers are
+  /// compared as numbers.
+  regex: String,
+}
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureField {
+  Title,
+  Body,
+  Link,
+  Author,
+  Guid,
+  Category,
+}
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Order {
+  /// Ascending: oldest first, A to Z
+  Asc,
+  /// Descending: newest first, Z to A
+  Desc,
+}
+
+impl From<SortConfig> for SortSpec {
+  fn from(config: SortConfig) -> Self {
+    match config {
+      SortConfig::Simple(by) => SortSpec {
+        by,
+        order: None,
+        then: None,
+      },
+      SortConfig::Full(spec) => spec,
+    }
+  }
+}
+
+pub struct Sort {
+  // in order of priority
+  keys: Vec<(Key, Order)>,
+}
+
+enum Key {
+  Field(SortField),
+  Capture(CaptureField, Regex),
+}
+
+// values of different kinds, e.g. numbers and text captured by the
+// same regex, are ordered by kind: dates, then numbers, then text
+enum Value {
+  Date(DateTime<FixedOffset>),
+  Number(f64),
+  Text(String),
+}
+
+#[async_trait::async_trait]
+impl FeedFilterConfig for SortConfig {
+  type Filter = Sort;
+
+  async fn build(self) -> Result<Self::Filter> {
+    let mut keys = vec![];
+    let mut spec = Some(SortSpec::from(self));
+
+    while let Some(SortSpec { by, order, then }) = spec {
+      let key = match by {
+        SortKey::Field(field) => Key::Field(field),
+        SortKey::Capture(CaptureKey { field, regex }) => {
+          Key::Capture(field, Regex::new(&regex)?)
+        }
+      };
+      let order = order.unwrap_or(match key {
+        Key::Field(SortField::Date) => Order::Desc,
+        _ => Order::Asc,
+      });
+      keys.push((key, order));
+      spec = then.map(|then| *then);
+    }
+
+    Ok(Sort { keys })
+  }
+}
+
+#[async_trait::async_trait]
+impl FeedFilter for Sort {
+  async fn run(
+    &self,
+    _ctx: &mut FilterContext,
+    mut feed: Feed,
+  ) -> Result<Feed> {
+    let mut posts: Vec<_> = feed
+      .take_posts()
+      .into_iter()
+      .map(|post| {
+        let values: Vec<_> =
+          self.keys.iter().map(|(key, _)| key.value(&post)).collect();
+        (values, post)
+      })
+      .collect();
+
+    // a stable sort keeps the original order for equal posts
+    posts.sort_by(|(a, _), (b, _)| {
+      self
+        .keys
+        .iter()
+        .zip(a.iter().zip(b))
+        .map(|((_, order), (a, b))| compare(a, b, *order))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+    });
+
+    feed.set_posts(posts.into_iter().map(|(_, post)| post).collect());
+    Ok(feed)
+  }
+}
+
+impl Key {
+  fn value(&self, post: &Post) -> Option<Value> {
+    let text = |s: &str| Value::Text(s.to_lowercase());
+
+    match self {
+      Self::Field(SortField::Date) => post.pub_date().map(Value::Date),
+      Self::Field(SortField::Title) => post.title().map(text),
+      Self::Field(SortField::Author) => post.author().map(text),
+      Self::Field(SortField::Category) => {
+        post.categories().first().copied().map(text)
+      }
+      Self::Capture(field, regex) => {
+        let haystacks = match field {
+          CaptureField::Title => post.title().into_iter().collect(),
+          CaptureField::Body => post.bodies(),
+          CaptureField::Link => post.link().into_iter().collect(),
+          CaptureField::Author => post.author().into_iter().collect(),
+          CaptureField::Guid => post.guid().into_iter().collect(),
+          CaptureField::Category => post.categories(),
+        };
+        let captures = haystacks.iter().find_map(|s| regex.captures(s))?;
+        let capture = captures.get(1).or_else(|| captures.get(0))?.as_str();
+
+        Some(Value::from_capture(capture))
+      }
+    }
+  }
+}
+
+impl Value {
+  // "nan" and "inf" parse as floats but are most likely words
+  fn from_capture(capture: &str) -> Self {
+    match capture.parse::<f64>() {
+      Ok(number) if number.is_finite() => Self::Number(number),
+      _ => Self::Text(capture.to_owned()),
+    }
+  }
+
+  fn rank(&self) -> u8 {
+    match self {
+      Self::Date(_) => 0,
+      Self::Number(_) => 1,
+      Self::Text(_) => 2,
+    }
+  }
+
+  fn cmp(&self, other: &Self) -> Ordering {
+    match (self, other) {
+      (Self::Date(a), Self::Date(b)) => a.cmp(b),
+      (Self::Number(a), Self::Number(b)) => a.total_cmp(b),
+      (Self::Text(a), Self::Text(b)) => a.cmp(b),
+      _ => self.rank().cmp(&other.rank()),
+    }
+  }
+}
+
+// posts without the key come last in either order
+fn compare(a: &Option<Value>, b: &Option<Value>, order: Order) -> Ordering {
+  match (a, b) {
+    (Some(a), Some(b)) => {
+      let ordering = a.cmp(b);
+      match order {
+        Order::Asc => ordering,
+        Order::Desc => ordering.reverse(),
+      }
+    }
+    (Some(_), None) => Ordering::Less,
+    (None, Some(_)) => Ordering::Greater,
+    (None, None) => Ordering::Equal,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::test_utils::fetch_duplicates_titles;
+
+  #[tokio::test]
+  async fn test_sort_by_date() {
+    assert_eq!(
+      fetch_duplicates_titles("sort", "date").await,
+      [
+        "Another story (updated)",
+        "Big news!",
+        "Another story, mirrored",
+        "Another story",
+        "Big News",
+      ]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_sort_by_title() {
+    assert_eq!(
+      fetch_duplicates_titles("sort", "{ by: title, order: desc }").await,
+      [
+        "Big news!",
+        "Big News",
+        "Another story, mirrored",
+        "Another story (updated)",
+        "Another story",
+      ]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_sort_by_capture_with_tie_breaker() {
+    let filter = r"
+            by: { field: link, regex: 'news/(\d+)' }
+            order: desc
+            then: { by: date }";
+    assert_eq!(
+      fetch_duplicates_titles("sort", filter).await,
+      [
+        "Another story (updated)",
+        "Another story",
+        "Big news!",
+        "Big News",
+        "Another story, mirrored",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_capture_values() {
+    let values: Vec<_> = ["10", "b", "nan", "2.5", "inf", "-1"]
+      .into_iter()
+      .map(Value::from_capture)
+      .collect();
+    let mut sorted: Vec<_> = values.iter().collect();
+    sorted.sort_by(|a, b| a.cmp(b));
+
+    let sorted: Vec<_> = sorted
+      .into_iter()
+      .map(|value| match value {
+        Value::Number(n) => n.to_string(),
+        Value::Text(s) => s.clone(),
+        Value::Date(_) => unreachable!(),
+      })
+      .collect();
+    assert_eq!(sorted, ["-1", "2.5", "10", "b", "inf", "nan"]);
+  }
+}
@@ -0,0 +1,171 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  error::Result,
+  feed::{Feed, Post},
+  filter_pipeline::{FilterPipeline, FilterPipelineConfig},
+};
+
+use super::{FeedFilter, FeedFilterConfig, FilterContext, expr::Expr};
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+/// Apply filters only to the posts matching an expression. See
+/// `keep_only` for the syntax of expressions.
+pub struct WhenConfig {
+  /// The condition on each post, e.g.
+  /// `link ~ "^https://example\.com/" or category == news`
+  expr: String,
+  /// Filters to apply to the matching posts
+  then: FilterPipelineConfig,
+  /// Filters to apply to the other posts, which are otherwise left
+  /// unchanged
+  #[serde(default, rename = "else")]
+  otherwise: FilterPipelineConfig,
+}
+
+pub struct When {
+  expr: Expr,
+  then: FilterPipeline,
+  otherwise: FilterPipeline,
+}
+
+#[async_trait::async_trait]
+impl FeedFilterConfig for WhenConfig {
+  type Filter = When;
+
+  async fn build(self) -> Result<Self::Filter> {
+    Ok(When {
+      expr: Expr::parse(&self.expr)?,
+      then: FilterPipeline::from_config(self.then).await?,
+      otherwise: FilterPipeline::from_config(self.otherwise).await?,
+    })
+  }
+}
+
+#[async_trait::async_trait]
+impl FeedFilter for When {
+  async fn run(&self, ctx: &mut FilterContext, mut feed: Feed) -> Result<Feed> {
+    let posts = feed.take_posts();
+    let matched: Vec<bool> =
+      posts.iter().map(|post| self.expr.matches(post)).collect();
+    let mut then_posts = vec![];
+    let mut else_posts = vec![];
+    for (post, matched) in posts.into_iter().zip(&matched) {
+      if *matched {
+        then_posts.push(post);
+      } else {
+        else_posts.push(post);
+      }
+    }
+
+    let mut subctx = ctx.subcontext();
+    let then_posts =
+      run_branch(&self.then, subctx.as_mut(), &feed, then_posts).await?;
+    let else_posts =
+      run_branch(&self.otherwise, subctx.as_mut(), &feed, else_posts).await?;
+    drop(subctx);
+
+    // keep the original order unless the filters added or removed
+    // posts, in which case the matching posts come first
+    let then_count = matched.iter().filter(|m| **m).count();
+    let posts = if then_posts.len() == then_count
+      && else_posts.len() == matched.len() - then_count
+    {
+      let mut then_posts = then_posts.into_iter();
+      let mut else_posts = else_posts.into_iter();
+      matched
+        .iter()
+        .filter_map(|m| {
+          if *m {
+            then_posts.next()
+          } else {
+            else_posts.next()
+          }
+        })
+        .collect()
+    } else {
+      then_posts.into_iter().chain(else_posts).collect()
+    };
+
+    feed.set_posts(posts);
+    Ok(feed)
+  }
+}
+
+async fn run_branch(
+  pipeline: &FilterPipeline,
+  ctx: &mut FilterContext,
+  feed: &Feed,
+  posts: Vec<Post>,
+) -> Result<Vec<Post>> {
+  let mut branch_feed = feed.clone();
+  branch_feed.set_posts(posts);
+  let mut branch_feed = pipeline.run(ctx, branch_feed).await?;
+
+  // the branch may have converted the feed, whose posts would
+  // otherwise be dropped when put back into the outer feed
+  let format = feed.format();
+  let posts = branch_feed.take_posts().into_iter();
+  Ok(posts.map(|post| post.into_format(format)).collect())
+}
+
+#[cfg(test)]
+mod test {
+  use crate::test_utils::fetch_duplicates_titles;
+
+  #[tokio::test]
+  async fn test_when_then_else() {
+    let filter = r#"
+            expr: 'link ~ "example\.com/news/1"'
+            then:
+              - modify_post: post.title = "[1] " + post.title;
+            else:
+              - modify_post: post.title = "[2] " + post.title;"#;
+
+    assert_eq!(
+      fetch_duplicates_titles("when", filter).await,
+      [
+        "[1] Big News",
+        "[1] Big news!",
+        "[2] Another story",
+        "[2] Another story, mirrored",
+        "[2] Another story (updated)",
+      ]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_when_changing_post_count() {
+    let filter = r"
+            expr: 'title ~ another'
+            then:
+              - limit: 1";
+
+    assert_eq!(
+      fetch_duplicates_titles("when", filter).await,
+      ["Another story", "Big News", "Big news!"]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_when_converting_format() {
+    let filter = r"
+            expr: 'title ~ another'
+            then:
+              - convert_to: atom";
+
+    assert_eq!(
+      fetch_duplicates_titles("when", filter).await,
+      [
+        "Big News",
+        "Big news!",
+        "Another story",
+        "Another story, mirrored",
+        "Another story (updated)",
+      ]
+    );
+  }
+}
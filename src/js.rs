@@ -270,19 +270,13 @@ impl RemoteLoader {
   }
 
   fn try_load_remote(&self, name: &str) -> rquickjs::Result<String> {
-    let client = reqwest::blocking::ClientBuilder::new()
-      .user_agent(crate::util::USER_AGENT)
-      .build()
-      .map_err(|_| rquickjs::Error::new_loading(name))?;
-
-    let source = client
-      .get(name)
-      .send()
-      .and_then(reqwest::blocking::Response::error_for_status)
-      .and_then(reqwest::blocking::Response::text)
-      .map_err(|_| rquickjs::Error::new_loading(name))?;
-
-    Ok(source)
+    let load = || {
+      let url = url::Url::parse(name)?;
+      crate::client::get_blocking(&url)?
+        .error_for_status()?
+        .text()
+    };
+    load().map_err(|_: anyhow::Error| rquickjs::Error::new_loading(name))
   }
 }
 
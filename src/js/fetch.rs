@@ -114,10 +114,12 @@ pub(super) async fn fetch(
     builder = builder.body(body);
   }
 
-  let resp = builder
-    .send()
+  let throw =
+    |e: &dyn std::fmt::Display| Exception::throw_message(&ctx, &e.to_string());
+  let req = builder.build().map_err(|e| throw(&e))?;
+  let resp = crate::client::send_request(&client, req)
     .await
-    .map_err(|e| Exception::throw_message(&ctx, &e.to_string()))?;
+    .map_err(|e| throw(&e))?;
   let status = resp.status().as_u16();
   let mut headers = HashMap::new();
 
@@ -125,10 +127,7 @@ pub(super) async fn fetch(
     headers.insert(k.as_str().to_string(), v.to_str().unwrap().to_string());
   }
 
-  let body = resp
-    .text()
-    .await
-    .map_err(|e| Exception::throw_message(&ctx, &e.to_string()))?;
+  let body = resp.text().map_err(|e| throw(&e))?;
 
   Ok(Response {
     status,
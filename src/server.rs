@@ -21,6 +21,7 @@ use tracing::{info, warn};
 
 use crate::{
   Result,
+  client::RecordingConfig,
   util::{self, relative_path},
 };
 
@@ -55,10 +56,14 @@ pub struct ServerConfig {
   /// Watch the config file for changes and restart the server
   #[clap(long, short, env = "RSS_FUNNEL_WATCH")]
   watch: bool,
+
+  #[clap(flatten)]
+  recording: RecordingConfig,
 }
 
 impl ServerConfig {
   pub async fn run(self, config_path: Option<&Path>) -> Result<()> {
+    self.recording.install();
     if let Some(config_path) = config_path {
       info!("loading config from {:?}", config_path);
       self.run_with_config(config_path).await
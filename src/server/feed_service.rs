@@ -60,6 +60,7 @@ impl FeedService {
       endpoints.insert(path, endpoint_service);
     }
 
+    root_config.install();
     let inner = Inner {
       config_path: config_path.map(PathBuf::from),
       config_error: None,
@@ -159,6 +160,7 @@ impl FeedService {
       }
     }
 
+    feed_defn.install();
     inner.root_config = Arc::new(feed_defn);
     inner.endpoints = endpoints;
     true
@@ -135,6 +135,34 @@ fn source_control_fragment(
       }
     },
     Source::FromScratch(scratch) => from_scratch_fragment(scratch),
+    Source::LocalFile(file) => html! {
+      div title="Source" .source { (file.path.display().to_string()) }
+    },
+    Source::Command(cmd) => html! {
+      div title="Source" .source { (cmd.command.join(" ")) }
+    },
+    Source::Mailbox(mailbox) => html! {
+      div title="Mailbox" .source { (mailbox.mailbox.display().to_string()) }
+    },
+    Source::Git(git) => html! {
+      div title="Git repository" .source { (git.git.display().to_string()) }
+    },
+    Source::Mirrors(mirrors) => html! {
+      div title="Mirrors" .source {
+        @for source in mirrors.sources() {
+          div { (source.to_string()) }
+        }
+      }
+    },
+    Source::Paginated(paginated) => html! {
+      div title="Source" .source { (paginated.url) }
+    },
+    Source::Scrape(scrape) => html! {
+      div title="Source" .source { (scrape.scrape) }
+    },
+    Source::Sitemap(sitemap) => html! {
+      div title="Sitemap" .source { (sitemap.sitemap) }
+    },
   }
 }
 
@@ -139,6 +139,64 @@ fn short_source_repr(source: &SourceConfig) -> Markup {
         span .tag.templated title="Templated source" { "templated" }
       }
     }
+    SourceConfig::LocalFile(file) => {
+      html! {
+        span .tag.local title=(file.path.display().to_string()) { "file" }
+      }
+    }
+    SourceConfig::Command(cmd) => {
+      html! {
+        span .tag.local title=(cmd.command.join(" ")) { "command" }
+      }
+    }
+    SourceConfig::Mailbox(mailbox) => {
+      html! {
+        span .tag.local title=(mailbox.mailbox.display().to_string()) {
+          "mailbox"
+        }
+      }
+    }
+    SourceConfig::Git(git) => {
+      html! {
+        span .tag.local title=(git.git.display().to_string()) { "git" }
+      }
+    }
+    SourceConfig::Mirrors(mirrors) => {
+      html! {
+        span .tag.simple title="Mirrors" {
+          @for mirror in &mirrors.mirrors {
+            (short_source_repr(mirror))
+          }
+        }
+      }
+    }
+    SourceConfig::Paginated(paginated) => {
+      let host =
+        url_host(paginated.url.as_str()).unwrap_or_else(|| "...".into());
+      html! {
+        span .tag.simple title="Paginated source" {
+          a href=(paginated.url) { (host) }
+        }
+      }
+    }
+    SourceConfig::Scrape(scrape) => {
+      let host =
+        url_host(scrape.scrape.as_str()).unwrap_or_else(|| "...".into());
+      html! {
+        span .tag.simple title="Scraped from HTML" {
+          a href=(scrape.scrape) { (host) }
+        }
+      }
+    }
+    SourceConfig::Sitemap(sitemap) => {
+      let host =
+        url_host(sitemap.sitemap.as_str()).unwrap_or_else(|| "...".into());
+      html! {
+        span .tag.simple title="Sitemap" {
+          a href=(sitemap.sitemap) { (host) }
+        }
+      }
+    }
   }
 }
 
@@ -1,4 +1,15 @@
-use std::collections::{BTreeMap, HashMap};
+mod command;
+mod git;
+mod mailbox;
+mod mirrors;
+mod pagination;
+mod scrape;
+mod sitemap;
+
+use std::{
+  collections::{BTreeMap, HashMap},
+  path::{Path, PathBuf},
+};
 
 use either::Either;
 use regex::Regex;
@@ -12,11 +23,22 @@ use crate::{
     DynamicSourceUnspecified, MissingSourceTemplatePlaceholder, Result,
     SourceTemplateValidation,
   },
-  feed::{Feed, FeedFormat},
+  feed::{Feed, FeedFormat, NormalizedPost},
   filter::FilterContext,
   server::EndpointParam,
 };
 
+pub use self::command::CommandSource;
+pub use self::git::GitSource;
+pub use self::mailbox::MailboxSource;
+pub use self::mirrors::{Mirrors, MirrorsConfig};
+pub use self::pagination::{NextPage, PaginatedSource, Pagination, page_url};
+pub use self::scrape::ScrapeSource;
+pub use self::sitemap::SitemapSource;
+
+// the number of posts kept by the sources reading local data
+const DEFAULT_LIMIT: usize = 50;
+
 lazy_static::lazy_static! {
   static ref VAR_RE: Regex = Regex::new(r"\$\{(?<name>\w+)\}").unwrap();
 }
@@ -40,6 +62,42 @@ pub enum SourceConfig {
   /// A source url that has placeholders that need to be filled in
   /// with values from the request.
   Templated(Templated),
+  /// # Local file
+  ///
+  /// A feed file on disk, re-read on every request. Can also be
+  /// written as a string starting with "file:".
+  LocalFile(LocalFile),
+  /// # Command
+  ///
+  /// Run a command and read the feed from its stdout.
+  Command(CommandSource),
+  /// # Scrape
+  ///
+  /// Build the feed from an HTML page using CSS selectors.
+  Scrape(ScrapeSource),
+  /// # Paginated source
+  ///
+  /// A feed url whose following pages are fetched and merged.
+  Paginated(PaginatedSource),
+  /// # Mirrors
+  ///
+  /// A list of sources for the same feed. The first one that succeeds
+  /// is used. A plain list of sources is tried in order.
+  Mirrors(MirrorsConfig),
+  /// # Sitemap
+  ///
+  /// Build the feed from the pages listed in a sitemap, most recently
+  /// modified first.
+  Sitemap(SitemapSource),
+  /// # Mailbox
+  ///
+  /// Read messages from a local Maildir or mbox file, one post per
+  /// message.
+  Mailbox(MailboxSource),
+  /// # Git repository
+  ///
+  /// The commits, and optionally the tags, of a local git repository.
+  Git(GitSource),
 }
 
 #[derive(
@@ -57,6 +115,10 @@ pub struct Templated {
   /// defines the value of the placeholder.
   // using BTreeMap instead of HashMap only because it implements Hash
   placeholders: BTreeMap<String, Placeholder>,
+  /// Fetch the following pages and merge them. Use `${page}` in the
+  /// template for paginating by page number.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pagination: Option<Pagination>,
 }
 
 #[derive(
@@ -78,7 +140,15 @@ impl Templated {
     &self,
     params: &HashMap<String, String>,
   ) -> Result<Source> {
-    let mut url = self.template.clone();
+    self.to_page_source(params, 1)
+  }
+
+  fn to_page_source(
+    &self,
+    params: &HashMap<String, String>,
+    page: usize,
+  ) -> Result<Source> {
+    let mut url = pagination::page_url(&self.template, page);
 
     for (name, placeholder) in &self.placeholders {
       let value = params
@@ -163,11 +233,30 @@ pub enum Source {
   Templated(Templated),
   #[error("{0:?}")]
   FromScratch(FromScratch),
+  #[error("{}", .0.path.display())]
+  LocalFile(LocalFile),
+  #[error("{:?}", .0.command)]
+  Command(CommandSource),
+  #[error("{}", .0.scrape)]
+  Scrape(ScrapeSource),
+  #[error("{}", .0.url)]
+  Paginated(PaginatedSource),
+  #[error("{0}")]
+  Mirrors(Mirrors),
+  #[error("{}", .0.sitemap)]
+  Sitemap(SitemapSource),
+  #[error("{}", .0.mailbox.display())]
+  Mailbox(MailboxSource),
+  #[error("{}", .0.git.display())]
+  Git(GitSource),
 }
 
 #[derive(
   JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
 )]
+// otherwise any source with a title and a format would be read as
+// one, as it is tried first
+#[serde(deny_unknown_fields)]
 pub struct FromScratch {
   /// The format of the feed
   pub format: FeedFormat,
@@ -179,6 +268,67 @@ pub struct FromScratch {
   pub description: Option<String>,
 }
 
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+pub struct LocalFile {
+  /// Path to an RSS, Atom or JSON Feed file. A relative path is
+  /// resolved against the directory of the config file.
+  pub path: PathBuf,
+}
+
+impl LocalFile {
+  fn from_file_url(s: &str) -> Option<Self> {
+    let path = s
+      .strip_prefix("file://")
+      .or_else(|| s.strip_prefix("file:"))?;
+    Some(Self { path: path.into() })
+  }
+
+  async fn read_feed(&self) -> Result<Feed> {
+    let path = crate::util::resolve_config_path(&self.path);
+    let content = tokio::fs::read(&path)
+      .await
+      .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))?;
+
+    feed_from_bytes(&content)
+  }
+}
+
+// Guess between JSON Feed and the XML formats by the first character.
+fn feed_from_bytes(content: &[u8]) -> Result<Feed> {
+  if content.trim_ascii_start().starts_with(b"{") {
+    Feed::from_json_content(content)
+  } else {
+    Feed::from_xml_content(content)
+  }
+}
+
+// Build the feed of a source that creates its posts itself.
+fn feed_from_posts(
+  format: Option<FeedFormat>,
+  title: String,
+  link: Option<String>,
+  posts: impl IntoIterator<Item = NormalizedPost>,
+) -> Feed {
+  let mut feed = Feed::from(&FromScratch {
+    format: format.unwrap_or(FeedFormat::Rss),
+    title,
+    link,
+    description: None,
+  });
+  for post in posts {
+    feed.add_post(post);
+  }
+  feed
+}
+
+// The name of a file or directory, as the default feed title.
+fn title_from_path(path: &Path) -> String {
+  let name = path.file_name().map(|name| name.to_string_lossy());
+  name.map_or_else(|| path.display().to_string(), |n| n.into_owned())
+}
+
 impl From<Url> for Source {
   fn from(url: Url) -> Self {
     Source::AbsoluteUrl(url)
@@ -210,6 +360,23 @@ impl TryFrom<SourceConfig> for Source {
         validate_placeholders(&config)?;
         Ok(Source::Templated(config))
       }
+      SourceConfig::LocalFile(config) => Ok(Source::LocalFile(config)),
+      SourceConfig::Command(config) => {
+        config.validate()?;
+        Ok(Source::Command(config))
+      }
+      SourceConfig::Scrape(config) => Ok(Source::Scrape(config.build()?)),
+      SourceConfig::Paginated(config) => {
+        config.validate()?;
+        Ok(Source::Paginated(config))
+      }
+      SourceConfig::Mirrors(config) => Ok(Source::Mirrors(config.try_into()?)),
+      SourceConfig::Sitemap(config) => {
+        config.validate()?;
+        Ok(Source::Sitemap(config))
+      }
+      SourceConfig::Mailbox(config) => Ok(Source::Mailbox(config)),
+      SourceConfig::Git(config) => Ok(Source::Git(config)),
       SourceConfig::Dynamic => Ok(Source::Dynamic),
     }
   }
@@ -228,10 +395,21 @@ fn validate_placeholders(config: &Templated) -> Result<()> {
     }
   }
 
+  if let Some(pagination) = &config.pagination {
+    pagination.validate(&config.template, &["next_link", "page"])?;
+  }
+  let paginate_by_page = config
+    .pagination
+    .as_ref()
+    .is_some_and(Pagination::is_by_page);
+
   // Validation: all placeholder patterns in template must be
   // defined in placeholders
   for cap in VAR_RE.captures_iter(&config.template) {
     let name = &cap["name"];
+    if paginate_by_page && name == "page" {
+      continue;
+    }
     if !config.placeholders.contains_key(name) {
       anyhow::bail!("placeholder ${{{name}}} is not defined");
     }
@@ -277,9 +455,35 @@ impl Source {
       }
       Source::FromScratch(config) => Ok(Feed::from(config)),
       Source::Templated(template) => {
-        let source = template.to_regular_source(context.extra_queries())?;
-        Box::pin(source.fetch_feed(context, client.ok())).await
+        let params = context.extra_queries();
+        let Some(pagination) = &template.pagination else {
+          let source = template.to_regular_source(params)?;
+          return Box::pin(source.fetch_feed(context, client.ok())).await;
+        };
+
+        let client = client?;
+        let page_url = |page| -> Result<Url> {
+          template
+            .to_page_source(params, page)?
+            .full_url(context)
+            .ok_or_else(|| anyhow::anyhow!("cannot resolve page url"))
+        };
+        pagination
+          .collect(page_url(1)?, |page, next| async move {
+            pagination::fetch_feed_page(client, next, || page_url(page)).await
+          })
+          .await
+      }
+      Source::LocalFile(file) => file.read_feed().await,
+      Source::Command(command) => feed_from_bytes(&command.run().await?),
+      Source::Scrape(scrape) => scrape.fetch_feed(client?).await,
+      Source::Paginated(paginated) => paginated.fetch_feed(client?).await,
+      Source::Mirrors(mirrors) => {
+        mirrors.fetch_feed(context, client.ok()).await
       }
+      Source::Sitemap(sitemap) => sitemap.fetch_feed(client?).await,
+      Source::Mailbox(mailbox) => mailbox.read_feed().await,
+      Source::Git(git) => git.read_feed().await,
     }
   }
 
@@ -293,6 +497,16 @@ impl Source {
         .map(|base| base.join(path).expect("failed to join base and path")),
       Source::FromScratch(_) => None,
       Source::Templated(_) => None,
+      Source::LocalFile(_) => None,
+      Source::Command(_) => None,
+      Source::Scrape(scrape) => Url::parse(&scrape.scrape).ok(),
+      Source::Paginated(paginated) => {
+        Url::parse(&pagination::page_url(&paginated.url, 1)).ok()
+      }
+      Source::Mirrors(mirrors) => mirrors.sources().first()?.full_url(ctx),
+      Source::Sitemap(sitemap) => Url::parse(&sitemap.sitemap).ok(),
+      Source::Mailbox(_) => None,
+      Source::Git(_) => None,
     }
   }
 }
@@ -325,6 +539,7 @@ fn split_with_delimiter<'a>(
 #[cfg(test)]
 mod test {
   use super::*;
+  use crate::error::AllMirrorsFailed;
 
   #[tokio::test]
   async fn test_fetch_feed_from_scratch_rss() {
@@ -360,6 +575,177 @@ description: "A test feed"
     assert_eq!(feed.format(), FeedFormat::Atom);
   }
 
+  #[tokio::test]
+  async fn test_fetch_feed_from_local_file() {
+    let path =
+      format!("{}/fixtures/sample_atom.xml", env!("CARGO_MANIFEST_DIR"));
+    let config: SourceConfig =
+      serde_yaml::from_str(&format!("file://{path}")).unwrap();
+    assert_eq!(
+      config,
+      SourceConfig::LocalFile(LocalFile { path: path.into() })
+    );
+
+    let source = Source::try_from(config).unwrap();
+    let ctx = FilterContext::new();
+    let feed: Feed = source.fetch_feed(&ctx, None).await.unwrap();
+    assert_eq!(feed.format(), FeedFormat::Atom);
+  }
+
+  #[tokio::test]
+  async fn test_fetch_feed_from_command() {
+    let path =
+      format!("{}/fixtures/minimal_rss_20.xml", env!("CARGO_MANIFEST_DIR"));
+    let config: SourceConfig =
+      serde_yaml::from_str(&format!("command: [cat, {path}]")).unwrap();
+
+    let source = Source::try_from(config).unwrap();
+    let ctx = FilterContext::new();
+    let feed: Feed = source.fetch_feed(&ctx, None).await.unwrap();
+    assert_eq!(feed.format(), FeedFormat::Rss);
+  }
+
+  #[tokio::test]
+  async fn test_fetch_feed_from_scrape() {
+    const YAML_CONFIG: &str = r#"
+scrape: "fixture:///news_page.html?content_type=text/html"
+title_selector: ".articles a.title"
+body_selector: ".articles .summary"
+date_selector: ".articles time"
+"#;
+
+    let config: SourceConfig = serde_yaml::from_str(YAML_CONFIG).unwrap();
+    let source = Source::try_from(config).unwrap();
+    let client = crate::client::ClientConfig::default()
+      .build(std::time::Duration::from_secs(1))
+      .unwrap();
+    let ctx = FilterContext::new();
+    let feed: Feed = source.fetch_feed(&ctx, Some(&client)).await.unwrap();
+
+    let feed = feed.normalize();
+    assert_eq!(feed.title, "Example News");
+    assert_eq!(feed.description.as_deref(), Some("The latest from Example"));
+    assert_eq!(feed.posts.len(), 2);
+    assert_eq!(feed.posts[1].title, "Second story");
+    assert_eq!(feed.posts[1].link, "https://example.com/second");
+    assert!(feed.posts[1].date.is_some());
+  }
+
+  #[test]
+  fn test_scrape_invalid_post_selector() {
+    const YAML_CONFIG: &str = r#"
+scrape: "https://example.com/news"
+title_selector: ".articles a.title"
+link_selector: ".articles a["
+"#;
+
+    let config: SourceConfig = serde_yaml::from_str(YAML_CONFIG).unwrap();
+    assert!(Source::try_from(config).is_err());
+  }
+
+  #[tokio::test]
+  async fn test_fetch_feed_from_sitemap() {
+    const YAML_CONFIG: &str = r#"
+sitemap: "fixture:///sitemap/index.xml"
+include_glob: "https://example.com/blog/**"
+limit: 2
+"#;
+
+    let config: SourceConfig = serde_yaml::from_str(YAML_CONFIG).unwrap();
+    let source = Source::try_from(config).unwrap();
+    let client = crate::client::ClientConfig::default()
+      .build(std::time::Duration::from_secs(1))
+      .unwrap();
+    let ctx = FilterContext::new();
+    let feed: Feed = source.fetch_feed(&ctx, Some(&client)).await.unwrap();
+
+    let feed = feed.normalize();
+    assert_eq!(feed.posts.len(), 2);
+    assert_eq!(feed.posts[0].title, "Rust & Feeds");
+    assert_eq!(
+      feed.posts[0].link,
+      "https://example.com/blog/rust-and-feeds"
+    );
+    assert_eq!(feed.posts[1].title, "second post");
+    assert!(feed.posts[1].date.is_some());
+  }
+
+  #[test]
+  fn test_parse_sources_with_title_and_format() {
+    let parse = |yaml: &str| -> SourceConfig {
+      serde_yaml::from_str(&format!("{yaml}\ntitle: News\nformat: atom"))
+        .unwrap()
+    };
+
+    assert!(matches!(
+      parse("mailbox: newsletters.mbox"),
+      SourceConfig::Mailbox(_)
+    ));
+    assert!(matches!(parse("git: repo"), SourceConfig::Git(_)));
+    assert!(matches!(
+      parse("sitemap: https://example.com/sitemap.xml"),
+      SourceConfig::Sitemap(_)
+    ));
+  }
+
+  #[tokio::test]
+  async fn test_fetch_feed_from_mailbox() {
+    let dir = format!("{}/fixtures/mail", env!("CARGO_MANIFEST_DIR"));
+    let ctx = FilterContext::new();
+
+    let yaml = format!(
+      "mailbox: {dir}/newsletters.mbox\nlist_id: weekly.news.example.com"
+    );
+    let config: SourceConfig = serde_yaml::from_str(&yaml).unwrap();
+    let source = Source::try_from(config).unwrap();
+    let feed = source.fetch_feed(&ctx, None).await.unwrap().normalize();
+    assert_eq!(feed.title, "newsletters.mbox");
+    assert_eq!(feed.posts.len(), 2);
+    assert_eq!(feed.posts[0].title, "Issue #2: Café");
+    assert_eq!(feed.posts[0].author.as_deref(), Some("Example Weekly"));
+    assert_eq!(feed.posts[1].link, "mid:issue1%40news.example.com");
+    assert!(
+      feed.posts[1]
+        .body
+        .as_ref()
+        .unwrap()
+        .contains("<b>first</b>")
+    );
+    assert!(feed.posts[1].date.is_some());
+
+    let yaml = format!("mailbox: {dir}/maildir\nfrom: '*@news.example.com'");
+    let config: SourceConfig = serde_yaml::from_str(&yaml).unwrap();
+    let source = Source::try_from(config).unwrap();
+    let feed = source.fetch_feed(&ctx, None).await.unwrap().normalize();
+    assert_eq!(feed.posts.len(), 2);
+    assert_eq!(feed.posts[0].title, "Unread issue");
+  }
+
+  #[tokio::test]
+  async fn test_fetch_feed_from_mirrors() {
+    let fixture =
+      format!("{}/fixtures/sample_atom.xml", env!("CARGO_MANIFEST_DIR"));
+    let ctx = FilterContext::new();
+
+    for race in [false, true] {
+      let yaml = format!(
+        "mirrors: [file:///nonexistent.xml, file://{fixture}]\nrace: {race}"
+      );
+      let config: SourceConfig = serde_yaml::from_str(&yaml).unwrap();
+      let source = Source::try_from(config).unwrap();
+      let feed = source.fetch_feed(&ctx, None).await.unwrap();
+      assert_eq!(feed.format(), FeedFormat::Atom);
+    }
+
+    let yaml = "[file:///nonexistent1.xml, file:///nonexistent2.xml]";
+    let config: SourceConfig = serde_yaml::from_str(yaml).unwrap();
+    let source = Source::try_from(config).unwrap();
+    let err = source.fetch_feed(&ctx, None).await.unwrap_err();
+    let failures = &err.downcast_ref::<AllMirrorsFailed>().unwrap().0;
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures[1].0, "/nonexistent2.xml");
+  }
+
   #[test]
   fn test_template_source_segmentation() {
     const YAML_CONFIG: &str = r#"
@@ -403,7 +789,11 @@ placeholders:
 mod serialization {
   // this custom deserialize implementation allows us to parse the
   // special value "dynamic" as a SourceConfig::Dynamic.
-  use super::{FromScratch, Result, SourceConfig, Templated};
+  use super::{
+    CommandSource, FromScratch, GitSource, LocalFile, MailboxSource,
+    MirrorsConfig, PaginatedSource, Result, ScrapeSource, SitemapSource,
+    SourceConfig, Templated,
+  };
   use serde::{Deserialize, Serialize, de::Deserializer, ser::Serializer};
 
   impl<'de> Deserialize<'de> for SourceConfig {
@@ -416,8 +806,19 @@ mod serialization {
       enum SourceConfigHelper {
         Null,
         Str(String),
+        // before the structs, which also accept a sequence of their
+        // fields, e.g. a one-element list as a LocalFile
+        List(Vec<SourceConfig>),
         FromScratch(FromScratch),
         Templated(Templated),
+        LocalFile(LocalFile),
+        Command(CommandSource),
+        Scrape(ScrapeSource),
+        Paginated(PaginatedSource),
+        Mirrors(MirrorsConfig),
+        Sitemap(SitemapSource),
+        Mailbox(MailboxSource),
+        Git(GitSource),
       }
 
       let helper = SourceConfigHelper::deserialize(deserializer)?;
@@ -427,11 +828,28 @@ mod serialization {
         SourceConfigHelper::Str(s) if s == "dynamic" => {
           Ok(SourceConfig::Dynamic)
         }
-        SourceConfigHelper::Str(s) => Ok(SourceConfig::Simple(s.to_string())),
+        SourceConfigHelper::Str(s) => match LocalFile::from_file_url(&s) {
+          Some(file) => Ok(SourceConfig::LocalFile(file)),
+          None => Ok(SourceConfig::Simple(s.to_string())),
+        },
         SourceConfigHelper::FromScratch(fs) => {
           Ok(SourceConfig::FromScratch(fs))
         }
         SourceConfigHelper::Templated(t) => Ok(SourceConfig::Templated(t)),
+        SourceConfigHelper::LocalFile(f) => Ok(SourceConfig::LocalFile(f)),
+        SourceConfigHelper::Command(c) => Ok(SourceConfig::Command(c)),
+        SourceConfigHelper::Scrape(s) => Ok(SourceConfig::Scrape(s)),
+        SourceConfigHelper::Paginated(p) => Ok(SourceConfig::Paginated(p)),
+        SourceConfigHelper::Mirrors(m) => Ok(SourceConfig::Mirrors(m)),
+        SourceConfigHelper::Sitemap(s) => Ok(SourceConfig::Sitemap(s)),
+        SourceConfigHelper::Mailbox(m) => Ok(SourceConfig::Mailbox(m)),
+        SourceConfigHelper::Git(g) => Ok(SourceConfig::Git(g)),
+        SourceConfigHelper::List(mirrors) => {
+          Ok(SourceConfig::Mirrors(MirrorsConfig {
+            mirrors,
+            race: false,
+          }))
+        }
       }
     }
   }
@@ -446,6 +864,14 @@ mod serialization {
         SourceConfig::Simple(url) => serializer.serialize_str(url),
         SourceConfig::FromScratch(fs) => fs.serialize(serializer),
         SourceConfig::Templated(t) => t.serialize(serializer),
+        SourceConfig::LocalFile(f) => f.serialize(serializer),
+        SourceConfig::Command(c) => c.serialize(serializer),
+        SourceConfig::Scrape(s) => s.serialize(serializer),
+        SourceConfig::Paginated(p) => p.serialize(serializer),
+        SourceConfig::Mirrors(m) => m.serialize(serializer),
+        SourceConfig::Sitemap(s) => s.serialize(serializer),
+        SourceConfig::Mailbox(m) => m.serialize(serializer),
+        SourceConfig::Git(g) => g.serialize(serializer),
       }
     }
   }
@@ -478,6 +904,33 @@ mod serialization {
       );
     }
 
+    #[test]
+    fn test_deserialize_local_file() {
+      let expected = SourceConfig::LocalFile(LocalFile {
+        path: "feeds/out.xml".into(),
+      });
+
+      let json = r#""file:feeds/out.xml""#;
+      let config: SourceConfig = serde_json::from_str(json).unwrap();
+      assert_eq!(config, expected);
+
+      let json = r#"{"path": "feeds/out.xml"}"#;
+      let config: SourceConfig = serde_json::from_str(json).unwrap();
+      assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn test_deserialize_list() {
+      let expected = SourceConfig::Mirrors(MirrorsConfig {
+        mirrors: vec![SourceConfig::Simple("https://a/feed".into())],
+        race: false,
+      });
+
+      let config: SourceConfig =
+        serde_yaml::from_str("[https://a/feed]").unwrap();
+      assert_eq!(config, expected);
+    }
+
     #[test]
     fn test_serialize_dynamic() {
       let config = SourceConfig::Dynamic;
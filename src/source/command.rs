@@ -0,0 +1,179 @@
+use std::{
+  collections::BTreeMap, path::PathBuf, process::Stdio, time::Duration,
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt as _};
+
+use crate::error::{CommandOutputTooLarge, CommandTimeout, Result};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_OUTPUT_SIZE: usize = 10 * 1024 * 1024;
+// only used to give some context when the command fails
+const MAX_STDERR_SIZE: usize = 4 * 1024;
+
+#[serde_with::skip_serializing_none]
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+pub struct CommandSource {
+  /// The program to run followed by its arguments. No shell is
+  /// involved, wrap the command in `["sh", "-c", "..."]` if you need
+  /// one.
+  pub command: Vec<String>,
+  /// Extra environment variables for the command
+  #[serde(default)]
+  pub env: BTreeMap<String, String>,
+  /// The working directory of the command. A relative path is resolved
+  /// against the directory of the config file.
+  #[serde(default)]
+  pub working_dir: Option<PathBuf>,
+  /// Kill the command if it doesn't finish in time (Format: "4s",
+  /// "10m", "1h", "1d"; Default: "30s")
+  #[serde(default)]
+  #[serde(deserialize_with = "duration_str::deserialize_option_duration")]
+  #[schemars(with = "String")]
+  pub timeout: Option<Duration>,
+  /// The maximum number of bytes read from stdout (Default: 10MiB)
+  #[serde(default)]
+  pub max_output_size: Option<usize>,
+}
+
+impl CommandSource {
+  pub fn validate(&self) -> Result<()> {
+    if self.command.is_empty() {
+      anyhow::bail!("command must not be empty");
+    }
+    Ok(())
+  }
+
+  /// Run the command and return its stdout.
+  pub async fn run(&self) -> Result<Vec<u8>> {
+    let timeout = self.timeout.unwrap_or(DEFAULT_TIMEOUT);
+    let program = self.command.first().cloned().unwrap_or_default();
+    tokio::time::timeout(timeout, self.run_inner())
+      .await
+      .map_err(|_| CommandTimeout(program, timeout))?
+  }
+
+  async fn run_inner(&self) -> Result<Vec<u8>> {
+    let Some((program, args)) = self.command.split_first() else {
+      anyhow::bail!("command must not be empty");
+    };
+
+    let mut cmd = tokio::process::Command::new(program);
+    cmd
+      .args(args)
+      .envs(&self.env)
+      .stdin(Stdio::null())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped())
+      // make sure the process doesn't outlive a timeout
+      .kill_on_drop(true);
+
+    if let Some(dir) = &self.working_dir {
+      cmd.current_dir(crate::util::resolve_config_path(dir));
+    }
+
+    let mut child = cmd
+      .spawn()
+      .map_err(|e| anyhow::anyhow!("failed to run {program}: {e}"))?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+
+    let max_output_size =
+      self.max_output_size.unwrap_or(DEFAULT_MAX_OUTPUT_SIZE);
+    // bail out as soon as stdout is too large, the child is killed
+    // when dropped
+    let stdout = async {
+      read_limited(stdout, max_output_size).await?.ok_or_else(|| {
+        CommandOutputTooLarge(program.clone(), max_output_size).into()
+      })
+    };
+    let (stdout, stderr) =
+      tokio::try_join!(stdout, read_stderr(stderr, MAX_STDERR_SIZE))?;
+
+    let status = child.wait().await?;
+    if !status.success() {
+      let stderr = String::from_utf8_lossy(&stderr).trim().to_owned();
+      anyhow::bail!("{program} exited with {status}: {stderr}");
+    }
+
+    Ok(stdout)
+  }
+}
+
+// Read up to `limit` bytes. Returns None if there is more.
+async fn read_limited(
+  reader: impl AsyncRead + Unpin,
+  limit: usize,
+) -> Result<Option<Vec<u8>>> {
+  let mut buf = Vec::new();
+  reader.take(limit as u64 + 1).read_to_end(&mut buf).await?;
+  Ok((buf.len() <= limit).then_some(buf))
+}
+
+// Read the first `limit` bytes of stderr. The rest is drained so the
+// child doesn't block on a full pipe.
+async fn read_stderr(
+  mut reader: impl AsyncRead + Unpin,
+  limit: usize,
+) -> Result<Vec<u8>> {
+  let mut buf = Vec::new();
+  (&mut reader)
+    .take(limit as u64)
+    .read_to_end(&mut buf)
+    .await?;
+  tokio::io::copy(&mut reader, &mut tokio::io::sink()).await?;
+  Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn command(args: &[&str]) -> CommandSource {
+    CommandSource {
+      command: args.iter().map(|s| s.to_string()).collect(),
+      env: BTreeMap::new(),
+      working_dir: None,
+      timeout: None,
+      max_output_size: None,
+    }
+  }
+
+  #[tokio::test]
+  async fn test_command_output() {
+    let mut cmd = command(&["sh", "-c", "printf %s \"$GREETING\""]);
+    cmd.env.insert("GREETING".into(), "hello".into());
+    assert_eq!(cmd.run().await.unwrap(), b"hello");
+  }
+
+  #[tokio::test]
+  async fn test_command_failure() {
+    let cmd = command(&["sh", "-c", "echo oops >&2; exit 3"]);
+    let err = cmd.run().await.unwrap_err().to_string();
+    assert!(err.contains("oops"), "{err}");
+  }
+
+  #[tokio::test]
+  async fn test_command_limits() {
+    let mut cmd = command(&["sh", "-c", "echo 0123456789"]);
+    cmd.max_output_size = Some(4);
+    let err = cmd.run().await.unwrap_err();
+    assert!(err.downcast_ref::<CommandOutputTooLarge>().is_some());
+
+    // stops reading instead of running into the timeout
+    let mut cmd = command(&["yes"]);
+    cmd.max_output_size = Some(1024);
+    cmd.timeout = Some(Duration::from_secs(10));
+    let err = cmd.run().await.unwrap_err();
+    assert!(err.downcast_ref::<CommandOutputTooLarge>().is_some());
+
+    let mut cmd = command(&["sleep", "5"]);
+    cmd.timeout = Some(Duration::from_millis(100));
+    let err = cmd.run().await.unwrap_err();
+    assert!(err.downcast_ref::<CommandTimeout>().is_some());
+  }
+}
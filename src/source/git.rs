@@ -0,0 +1,232 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use chrono::DateTime;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  error::Result,
+  feed::{Feed, FeedFormat, NormalizedPost},
+};
+
+use super::{CommandSource, DEFAULT_LIMIT, feed_from_posts, title_from_path};
+
+const FIELD_SEP: char = '\x1f';
+const RECORD_SEP: char = '\x1e';
+
+// %x1f and %x1e separate the fields and the records
+const LOG_FORMAT: &str = "--format=%H%x1f%an%x1f%aI%x1f%s%x1f%b%x1e";
+const TAG_FORMAT: &str = "--format=%(refname:short)%1f\
+  %(if)%(taggername)%(then)%(taggername)%(else)%(authorname)%(end)%1f\
+  %(creatordate:iso-strict)%1f%(contents:subject)%1f%(contents:body)%1e";
+
+#[serde_with::skip_serializing_none]
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+pub struct GitSource {
+  /// Path to a local git repository. A relative path is resolved
+  /// against the directory of the config file.
+  pub git: PathBuf,
+  /// The branch or revision to read the commits from (Default: HEAD)
+  #[serde(default)]
+  pub branch: Option<String>,
+  /// Also include the tags as posts
+  #[serde(default)]
+  pub tags: bool,
+  /// The link of a commit post, `${hash}` is replaced with the commit
+  /// hash (e.g. "https://git.example.com/repo/commit/${hash}").
+  /// Defaults to the commit hash itself.
+  #[serde(default)]
+  pub commit_link: Option<String>,
+  /// The link of a tag post, `${tag}` is replaced with the tag name
+  /// (e.g. "https://git.example.com/repo/releases/${tag}"). Defaults
+  /// to the tag name itself.
+  #[serde(default)]
+  pub tag_link: Option<String>,
+  /// The title of the feed (Default: the name of the repository
+  /// directory)
+  #[serde(default)]
+  pub title: Option<String>,
+  /// The format of the feed (Default: rss)
+  #[serde(default)]
+  pub format: Option<FeedFormat>,
+  /// Keep the most recent commits and tags (Default: 50)
+  #[serde(default)]
+  pub limit: Option<usize>,
+}
+
+impl GitSource {
+  pub async fn read_feed(&self) -> Result<Feed> {
+    let limit = self.limit.unwrap_or(DEFAULT_LIMIT);
+    let count = format!("--max-count={limit}");
+    let branch = self.branch.as_deref().unwrap_or("HEAD");
+
+    let log = self.git(&["log", &count, LOG_FORMAT, branch, "--"]).await?;
+    let mut posts: Vec<_> = records(&log)
+      .map(|[hash, author, date, subject, body]| {
+        let link = match &self.commit_link {
+          Some(template) => template.replace("${hash}", hash),
+          None => hash.to_owned(),
+        };
+        post(link, subject, author, date, body, "commit")
+      })
+      .collect();
+
+    if self.tags {
+      let count = format!("--count={limit}");
+      let sort = "--sort=-creatordate";
+      let tags = self
+        .git(&["for-each-ref", sort, &count, TAG_FORMAT, "refs/tags"])
+        .await?;
+      posts.extend(records(&tags).map(|[tag, author, date, subject, body]| {
+        let link = match &self.tag_link {
+          Some(template) => template.replace("${tag}", tag),
+          None => tag.to_owned(),
+        };
+        let title = match subject {
+          "" => tag.to_owned(),
+          subject => format!("{tag}: {subject}"),
+        };
+        post(link, &title, author, date, body, "tag")
+      }));
+    }
+
+    // most recent first
+    posts.sort_by_key(|post| std::cmp::Reverse(post.date));
+    posts.truncate(limit);
+
+    let path = crate::util::resolve_config_path(&self.git);
+    let title = self.title.clone().unwrap_or_else(|| title_from_path(&path));
+    Ok(feed_from_posts(self.format, title, None, posts))
+  }
+
+  async fn git(&self, args: &[&str]) -> Result<String> {
+    let command = std::iter::once("git")
+      .chain(args.iter().copied())
+      .map(String::from)
+      .collect();
+    let output = CommandSource {
+      command,
+      env: BTreeMap::new(),
+      working_dir: Some(self.git.clone()),
+      timeout: None,
+      max_output_size: None,
+    }
+    .run()
+    .await?;
+
+    Ok(String::from_utf8_lossy(&output).into_owned())
+  }
+}
+
+fn records(output: &str) -> impl Iterator<Item = [&str; 5]> {
+  output
+    .split(RECORD_SEP)
+    .map(|record| record.trim_start_matches('\n'))
+    .filter(|record| !record.is_empty())
+    .map(|record| {
+      let mut fields = record.split(FIELD_SEP);
+      std::array::from_fn(|_| fields.next().unwrap_or_default())
+    })
+}
+
+fn post(
+  link: String,
+  title: &str,
+  author: &str,
+  date: &str,
+  body: &str,
+  category: &str,
+) -> NormalizedPost {
+  let body = body.trim();
+  NormalizedPost {
+    title: title.to_owned(),
+    link,
+    body: (!body.is_empty())
+      .then(|| format!("<pre>{}</pre>", htmlescape::encode_minimal(body))),
+    date: DateTime::parse_from_rfc3339(date).ok(),
+    author: (!author.is_empty()).then(|| author.to_owned()),
+    categories: vec![category.to_owned()],
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::process::Command;
+
+  use super::*;
+
+  fn git(repo: &std::path::Path, args: &[&str], date: &str) {
+    let status = Command::new("git")
+      .args([
+        "-c",
+        "user.name=Jane Doe",
+        "-c",
+        "user.email=jane@example.com",
+      ])
+      .args(["-c", "tag.gpgSign=false", "-c", "commit.gpgSign=false"])
+      .args(args)
+      .current_dir(repo)
+      .env("GIT_AUTHOR_DATE", date)
+      .env("GIT_COMMITTER_DATE", date)
+      .status()
+      .unwrap();
+    assert!(status.success());
+  }
+
+  #[tokio::test]
+  async fn test_read_commits_and_tags() {
+    let repo = std::env::temp_dir()
+      .join(format!("rss-funnel-git-test-{}", std::process::id()));
+    std::fs::create_dir_all(&repo).unwrap();
+
+    let first = "2024-01-01T10:00:00+00:00";
+    let second = "2024-02-01T10:00:00+00:00";
+    let third = "2024-03-01T10:00:00+00:00";
+    git(&repo, &["init", "-q", "-b", "main"], first);
+    git(
+      &repo,
+      &["commit", "-q", "--allow-empty", "-m", "Initial"],
+      first,
+    );
+    git(
+      &repo,
+      &[
+        "commit",
+        "-q",
+        "--allow-empty",
+        "-m",
+        "Add <feature>\n\nDetails",
+      ],
+      second,
+    );
+    git(&repo, &["tag", "-a", "v1.0", "-m", "First release"], third);
+
+    let source = GitSource {
+      git: repo.clone(),
+      branch: None,
+      tags: true,
+      commit_link: Some("https://git.example.com/c/${hash}".into()),
+      tag_link: None,
+      title: None,
+      format: None,
+      limit: None,
+    };
+    let feed = source.read_feed().await;
+    std::fs::remove_dir_all(&repo).unwrap();
+
+    let feed = feed.unwrap().normalize();
+    assert_eq!(feed.posts.len(), 3);
+    assert_eq!(feed.posts[0].title, "v1.0: First release");
+    assert_eq!(feed.posts[0].link, "v1.0");
+
+    let commit = &feed.posts[1];
+    assert_eq!(commit.title, "Add <feature>");
+    assert_eq!(commit.author.as_deref(), Some("Jane Doe"));
+    assert_eq!(commit.body.as_deref(), Some("<pre>Details</pre>"));
+    assert!(commit.link.starts_with("https://git.example.com/c/"));
+    assert_eq!(commit.date.unwrap().to_rfc3339(), second);
+    assert_eq!(feed.posts[2].title, "Initial");
+  }
+}
@@ -0,0 +1,145 @@
+use std::path::{Path, PathBuf};
+
+use chrono::DateTime;
+use glob_match::glob_match;
+use mail_parser::{HeaderName, Message, MessageParser, mailbox};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  error::Result,
+  feed::{Feed, FeedFormat, NormalizedPost},
+  util::SingleOrVec,
+};
+
+use super::{DEFAULT_LIMIT, feed_from_posts, title_from_path};
+
+#[serde_with::skip_serializing_none]
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+pub struct MailboxSource {
+  /// Path to a Maildir directory or an mbox file. A relative path is
+  /// resolved against the directory of the config file.
+  pub mailbox: PathBuf,
+  /// The title of the feed (Default: the name of the mailbox)
+  #[serde(default)]
+  pub title: Option<String>,
+  /// The format of the feed (Default: rss)
+  #[serde(default)]
+  pub format: Option<FeedFormat>,
+  /// Only include messages from these sender addresses. Glob patterns
+  /// are supported (e.g. "*@substack.com").
+  #[serde(default)]
+  pub from: SingleOrVec<String>,
+  /// Only include messages whose List-Id header contains one of these
+  /// values (e.g. "weekly.example.com")
+  #[serde(default)]
+  pub list_id: SingleOrVec<String>,
+  /// Keep the most recent messages (Default: 50)
+  #[serde(default)]
+  pub limit: Option<usize>,
+}
+
+impl MailboxSource {
+  pub async fn read_feed(&self) -> Result<Feed> {
+    let path = crate::util::resolve_config_path(&self.mailbox);
+    let messages = read_messages(&path).await?;
+    let parser = MessageParser::default();
+
+    let mut posts: Vec<_> = messages
+      .iter()
+      .filter_map(|raw| parser.parse(raw))
+      .filter(|message| self.matches(message))
+      .map(|message| to_post(&message))
+      .collect();
+    // most recent first, messages without a date last
+    posts.sort_by_key(|post| std::cmp::Reverse(post.date));
+    posts.truncate(self.limit.unwrap_or(DEFAULT_LIMIT));
+
+    let title = self.title.clone().unwrap_or_else(|| title_from_path(&path));
+    Ok(feed_from_posts(self.format, title, None, posts))
+  }
+
+  fn matches(&self, message: &Message) -> bool {
+    let sender = message
+      .from()
+      .and_then(|from| from.first())
+      .and_then(|addr| addr.address())
+      .map(str::to_ascii_lowercase);
+    let list_id = message.header_raw(HeaderName::ListId);
+
+    let from_matches = is_empty(&self.from)
+      || sender.is_some_and(|sender| {
+        (&self.from)
+          .into_iter()
+          .any(|pat| glob_match(&pat.to_ascii_lowercase(), &sender))
+      });
+    let list_id_matches = is_empty(&self.list_id)
+      || list_id.is_some_and(|list_id| {
+        (&self.list_id).into_iter().any(|id| list_id.contains(id))
+      });
+
+    from_matches && list_id_matches
+  }
+}
+
+fn is_empty<T>(list: &SingleOrVec<T>) -> bool {
+  list.into_iter().next().is_none()
+}
+
+// A directory is read as a Maildir, anything else as an mbox file.
+async fn read_messages(path: &Path) -> Result<Vec<Vec<u8>>> {
+  let context = |e| anyhow::anyhow!("failed to read {}: {e}", path.display());
+
+  if tokio::fs::metadata(path).await.map_err(context)?.is_dir() {
+    return read_maildir(path).await.map_err(context);
+  }
+
+  let content = tokio::fs::read(path).await.map_err(context)?;
+  mailbox::mbox::MessageIterator::new(content.as_slice())
+    .map(|message| match message {
+      Ok(message) => Ok(message.unwrap_contents()),
+      Err(_) => anyhow::bail!("malformed mbox file {}", path.display()),
+    })
+    .collect()
+}
+
+// The messages in the "cur" and "new" folders, skipping hidden files.
+async fn read_maildir(path: &Path) -> std::io::Result<Vec<Vec<u8>>> {
+  let mut messages = vec![];
+  for folder in ["cur", "new"] {
+    let mut entries = tokio::fs::read_dir(path.join(folder)).await?;
+    while let Some(entry) = entries.next_entry().await? {
+      let hidden = entry.file_name().to_string_lossy().starts_with('.');
+      if hidden || !tokio::fs::metadata(entry.path()).await?.is_file() {
+        continue;
+      }
+      messages.push(tokio::fs::read(entry.path()).await?);
+    }
+  }
+  Ok(messages)
+}
+
+fn to_post(message: &Message) -> NormalizedPost {
+  let from = message.from().and_then(|from| from.first());
+  let author = from.and_then(|addr| addr.name().or(addr.address()));
+  let date = message
+    .date()
+    .and_then(|date| DateTime::parse_from_rfc3339(&date.to_rfc3339()).ok());
+  // RFC 2392 message id url, as emails have no link of their own
+  let link = message
+    .message_id()
+    .map(|id| format!("mid:{}", urlencoding::encode(id)))
+    .unwrap_or_default();
+
+  NormalizedPost {
+    title: message.subject().unwrap_or("(no subject)").to_owned(),
+    link,
+    // falls back to the text part converted to HTML
+    body: message.body_html(0).map(|body| body.into_owned()),
+    date,
+    author: author.map(ToOwned::to_owned),
+    categories: vec![],
+  }
+}
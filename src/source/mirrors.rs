@@ -0,0 +1,112 @@
+use std::fmt;
+
+use futures::{StreamExt as _, stream::FuturesUnordered};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{
+  client::Client,
+  error::{AllMirrorsFailed, Result},
+  feed::Feed,
+  filter::FilterContext,
+};
+
+use super::{Source, SourceConfig};
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+pub struct MirrorsConfig {
+  /// The sources to try. The first one that succeeds is used.
+  pub mirrors: Vec<SourceConfig>,
+  /// Fetch from all mirrors at the same time instead of trying them
+  /// one by one in order
+  #[serde(default)]
+  pub race: bool,
+}
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+pub struct Mirrors {
+  sources: Vec<Source>,
+  race: bool,
+}
+
+impl TryFrom<MirrorsConfig> for Mirrors {
+  type Error = anyhow::Error;
+
+  fn try_from(config: MirrorsConfig) -> Result<Self> {
+    if config.mirrors.is_empty() {
+      anyhow::bail!("mirrors must not be empty");
+    }
+
+    let sources = config
+      .mirrors
+      .into_iter()
+      .map(Source::try_from)
+      .collect::<Result<_>>()?;
+
+    Ok(Self {
+      sources,
+      race: config.race,
+    })
+  }
+}
+
+impl fmt::Display for Mirrors {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (i, source) in self.sources.iter().enumerate() {
+      if i > 0 {
+        write!(f, " | ")?;
+      }
+      write!(f, "{source}")?;
+    }
+    Ok(())
+  }
+}
+
+impl Mirrors {
+  pub fn sources(&self) -> &[Source] {
+    &self.sources
+  }
+
+  pub async fn fetch_feed(
+    &self,
+    context: &FilterContext,
+    client: Option<&Client>,
+  ) -> Result<Feed> {
+    let mut failures = vec![];
+
+    if self.race {
+      let mut pending: FuturesUnordered<_> = self
+        .sources
+        .iter()
+        .map(|source| async move {
+          let res = Box::pin(source.fetch_feed(context, client)).await;
+          (source, res)
+        })
+        .collect();
+
+      while let Some((source, res)) = pending.next().await {
+        match res {
+          Ok(feed) => return Ok(feed),
+          Err(e) => failures.push((source.to_string(), format!("{e:#}"))),
+        }
+      }
+    } else {
+      for source in &self.sources {
+        match Box::pin(source.fetch_feed(context, client)).await {
+          Ok(feed) => return Ok(feed),
+          Err(e) => {
+            warn!("mirror {source} failed: {e:#}");
+            failures.push((source.to_string(), format!("{e:#}")));
+          }
+        }
+      }
+    }
+
+    Err(AllMirrorsFailed(failures).into())
+  }
+}
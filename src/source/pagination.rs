@@ -0,0 +1,293 @@
+use std::{collections::HashSet, future::Future};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use url::Url;
+
+use crate::{client::Client, error::Result, feed::Feed};
+
+const DEFAULT_MAX_PAGES: usize = 5;
+pub const PAGE_PLACEHOLDER: &str = "${page}";
+
+/// How the next page is found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NextPage<'a> {
+  Link,
+  Selector(&'a str),
+  JsonPath(&'a str),
+  Page,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, Default,
+)]
+pub struct Pagination {
+  /// Follow the `rel="next"` link of the feed (RFC 5005), or
+  /// `next_url` for JSON Feed
+  #[serde(default)]
+  pub next_link: bool,
+  /// The CSS selector for the &lt;a&gt; element linking to the next
+  /// page
+  #[serde(default)]
+  pub next_selector: Option<String>,
+  /// The JSON path to the url of the next page
+  #[serde(default)]
+  pub next_json_path: Option<String>,
+  /// The maximum number of pages to fetch (Default: 5)
+  #[serde(default)]
+  pub max_pages: Option<usize>,
+  /// Stop fetching more pages once this many items are collected
+  #[serde(default)]
+  pub max_items: Option<usize>,
+}
+
+impl Pagination {
+  /// Without any of the `next_*` options, pages are fetched by
+  /// substituting `${page}` in the url with the page number.
+  pub fn next_page(&self) -> NextPage<'_> {
+    if self.next_link {
+      NextPage::Link
+    } else if let Some(selector) = &self.next_selector {
+      NextPage::Selector(selector)
+    } else if let Some(path) = &self.next_json_path {
+      NextPage::JsonPath(path)
+    } else {
+      NextPage::Page
+    }
+  }
+
+  /// Check the pagination method is one the source knows how to
+  /// follow. For paginating by page number, the url must contain the
+  /// `${page}` placeholder.
+  pub fn validate(&self, url: &str, supported: &[&str]) -> Result<()> {
+    let methods = usize::from(self.next_link)
+      + usize::from(self.next_selector.is_some())
+      + usize::from(self.next_json_path.is_some());
+    if methods > 1 {
+      anyhow::bail!(
+        "only one of next_link, next_selector and next_json_path can be set"
+      );
+    }
+
+    let method = match self.next_page() {
+      NextPage::Link => "next_link",
+      NextPage::Selector(selector) => {
+        crate::filter::html::parse_selector(selector)?;
+        "next_selector"
+      }
+      NextPage::JsonPath(path) => {
+        jsonpath_lib::Compiled::compile(path)
+          .map_err(|e| anyhow::anyhow!("invalid json path {path}: {e}"))?;
+        "next_json_path"
+      }
+      NextPage::Page => {
+        if !url.contains(PAGE_PLACEHOLDER) {
+          anyhow::bail!(
+            "{PAGE_PLACEHOLDER} is not present in {url}, or specify how to find the next page"
+          );
+        }
+        "page"
+      }
+    };
+
+    if !supported.contains(&method) {
+      anyhow::bail!(
+        "pagination by {method} is not supported here, use one of: {}",
+        supported.join(", ")
+      );
+    }
+
+    Ok(())
+  }
+
+  pub fn is_by_page(&self) -> bool {
+    self.next_page() == NextPage::Page
+  }
+
+  /// Fetch the pages one after another and merge them into the first
+  /// page, whose url is `first`. `fetch_page` is given the page
+  /// number, starting from 1, and the url the previous page links to,
+  /// if any. It returns the page and the url of the page after it.
+  pub async fn collect<F, Fut>(
+    &self,
+    first: Url,
+    mut fetch_page: F,
+  ) -> Result<Feed>
+  where
+    F: FnMut(usize, Option<Url>) -> Fut,
+    Fut: Future<Output = Result<(Feed, Option<Url>)>>,
+  {
+    let max_pages = self.max_pages.unwrap_or(DEFAULT_MAX_PAGES);
+    let mut visited = HashSet::from([first]);
+    let (mut feed, mut next) = fetch_page(1, None).await?;
+
+    for page in 2..=max_pages {
+      if self.max_items.is_some_and(|n| feed.post_count() >= n) {
+        break;
+      }
+
+      let url = match next.take() {
+        _ if self.is_by_page() => None,
+        // guard against pages linking back to each other
+        Some(url) if visited.insert(url.clone()) => Some(url),
+        _ => break,
+      };
+
+      let (page_feed, page_next) = match fetch_page(page, url).await {
+        Ok(res) => res,
+        Err(e) => {
+          warn!("stopped paginating at page {page}: {e}");
+          break;
+        }
+      };
+
+      if page_feed.post_count() == 0 {
+        break;
+      }
+
+      feed.merge(page_feed)?;
+      next = page_next;
+    }
+
+    if let Some(max_items) = self.max_items {
+      let mut posts = feed.take_posts();
+      posts.truncate(max_items);
+      feed.set_posts(posts);
+    }
+
+    Ok(feed)
+  }
+}
+
+pub fn page_url(url: &str, page: usize) -> String {
+  url.replace(PAGE_PLACEHOLDER, &page.to_string())
+}
+
+/// Fetch a feed and the url of its next page from the feed's own
+/// pagination link. The url is either the one the previous page links
+/// to, if any, or the source url for the page.
+pub async fn fetch_feed_page(
+  client: &Client,
+  next: Option<Url>,
+  page_url: impl FnOnce() -> Result<Url>,
+) -> Result<(Feed, Option<Url>)> {
+  let (feed, url) = match next {
+    Some(url) => (client.fetch_linked_feed(&url).await?, url),
+    None => {
+      let url = page_url()?;
+      (client.fetch_feed(&url).await?, url)
+    }
+  };
+  let next = feed.next_page_url().and_then(|next| url.join(next).ok());
+  Ok((feed, next))
+}
+
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+pub struct PaginatedSource {
+  /// The url of the first page. May contain `${page}` when paginating
+  /// by page number.
+  pub url: String,
+  /// How to follow the pages
+  pub pagination: Pagination,
+}
+
+impl PaginatedSource {
+  pub fn validate(&self) -> Result<()> {
+    Url::parse(&page_url(&self.url, 1))?;
+    self.pagination.validate(&self.url, &["next_link", "page"])
+  }
+
+  pub async fn fetch_feed(&self, client: &Client) -> Result<Feed> {
+    let first = Url::parse(&page_url(&self.url, 1))?;
+    self
+      .pagination
+      .collect(first, |page, next| async move {
+        fetch_feed_page(client, next, || {
+          Ok(Url::parse(&page_url(&self.url, page))?)
+        })
+        .await
+      })
+      .await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::source::FromScratch;
+
+  fn feed_with_posts(n: usize) -> Feed {
+    let mut feed = Feed::from(&FromScratch {
+      format: crate::feed::FeedFormat::Rss,
+      title: "test".into(),
+      link: None,
+      description: None,
+    });
+    for i in 0..n {
+      feed.add_post(crate::feed::NormalizedPost {
+        title: format!("post {i}"),
+        link: format!("https://example.com/{i}"),
+        ..Default::default()
+      });
+    }
+    feed
+  }
+
+  fn url(page: usize) -> Url {
+    Url::parse(&format!("https://example.com/?page={page}")).unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_collect_stops_at_limits() {
+    let pagination = Pagination {
+      max_pages: Some(3),
+      ..Default::default()
+    };
+    let feed = pagination
+      .collect(url(1), |_, _| async { Ok((feed_with_posts(2), None)) })
+      .await
+      .unwrap();
+    assert_eq!(feed.post_count(), 6);
+
+    let pagination = Pagination {
+      max_items: Some(3),
+      ..pagination
+    };
+    let feed = pagination
+      .collect(url(1), |_, _| async { Ok((feed_with_posts(2), None)) })
+      .await
+      .unwrap();
+    assert_eq!(feed.post_count(), 3);
+  }
+
+  #[tokio::test]
+  async fn test_collect_follows_links() {
+    let pagination = Pagination {
+      next_link: true,
+      max_pages: Some(10),
+      ..Default::default()
+    };
+
+    // page 3 links back to page 2, which was already visited
+    let feed = pagination
+      .collect(url(1), |page, _| async move {
+        Ok((feed_with_posts(1), Some(url(page.min(2) + 1))))
+      })
+      .await
+      .unwrap();
+    assert_eq!(feed.post_count(), 3);
+
+    // page 2 links back to the first page
+    let feed = pagination
+      .collect(url(1), |page, _| async move {
+        Ok((feed_with_posts(1), Some(url(3 - page))))
+      })
+      .await
+      .unwrap();
+    assert_eq!(feed.post_count(), 2);
+  }
+}
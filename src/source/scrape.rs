@@ -0,0 +1,212 @@
+use std::{
+  hash::{Hash, Hasher},
+  sync::Arc,
+};
+
+use schemars::JsonSchema;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+  client::Client,
+  error::Result,
+  feed::{Feed, FeedFormat, NormalizedPost},
+  filter::html::{Split, SplitConfig, parse_selector},
+};
+
+use super::{
+  FromScratch,
+  pagination::{NextPage, Pagination, page_url},
+};
+
+#[serde_with::skip_serializing_none]
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+pub struct ScrapeSource {
+  /// The url of the HTML page to build the feed from. May contain
+  /// `${page}` when paginating by page number.
+  pub scrape: String,
+  /// The format of the feed (Default: rss)
+  #[serde(default)]
+  pub format: Option<FeedFormat>,
+  /// The CSS selector for the feed title. The textContent of the first
+  /// selected element will be used. Defaults to the page's
+  /// &lt;title&gt;.
+  #[serde(default)]
+  pub feed_title_selector: Option<String>,
+  /// The CSS selector for the feed description. The textContent of
+  /// the first selected element will be used. Defaults to the page's
+  /// "description" meta tag.
+  #[serde(default)]
+  pub feed_description_selector: Option<String>,
+  /// Selectors for the posts, same as the `split` filter
+  #[serde(flatten)]
+  pub posts: SplitConfig,
+  /// Fetch the following pages and merge them
+  #[serde(default)]
+  pub pagination: Option<Pagination>,
+  #[serde(skip)]
+  #[schemars(skip)]
+  split: CompiledSplit,
+}
+
+// The post selectors, parsed once when the source is built instead of
+// on every fetch. Not part of the config, so it doesn't affect
+// equality or hashing.
+#[derive(Clone, Default)]
+struct CompiledSplit(Option<Arc<Split>>);
+
+impl std::fmt::Debug for CompiledSplit {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("CompiledSplit")
+  }
+}
+
+impl PartialEq for CompiledSplit {
+  fn eq(&self, _other: &Self) -> bool {
+    true
+  }
+}
+
+impl Eq for CompiledSplit {}
+
+impl Hash for CompiledSplit {
+  fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+impl ScrapeSource {
+  /// Validate the config and parse the post selectors.
+  pub fn build(mut self) -> Result<Self> {
+    self.validate()?;
+    self.split = CompiledSplit(Some(Arc::new(self.posts.compile()?)));
+    Ok(self)
+  }
+
+  fn validate(&self) -> Result<()> {
+    Url::parse(&page_url(&self.scrape, 1))?;
+    if let Some(pagination) = &self.pagination {
+      pagination.validate(&self.scrape, &["next_selector", "page"])?;
+    }
+    for selector in [&self.feed_title_selector, &self.feed_description_selector]
+      .into_iter()
+      .flatten()
+    {
+      parse_selector(selector)?;
+    }
+    Ok(())
+  }
+
+  pub async fn fetch_feed(&self, client: &Client) -> Result<Feed> {
+    let split = match &self.split.0 {
+      Some(split) => split.clone(),
+      None => Arc::new(self.posts.compile()?),
+    };
+    let split = split.as_ref();
+    let Some(pagination) = &self.pagination else {
+      let url = Url::parse(&self.scrape)?;
+      let html = client.get_source(&url).await?.error_for_status()?.text()?;
+      let (feed, _next) = self.parse_page(split, html, &url)?;
+      return Ok(feed);
+    };
+
+    let first = Url::parse(&page_url(&self.scrape, 1))?;
+    pagination
+      .collect(first, |page, next| async move {
+        // pages linked from the previous one are always fetched with GET
+        let (url, resp) = match next {
+          Some(url) => {
+            let resp = client.get(&url).await?;
+            (url, resp)
+          }
+          None => {
+            let url = Url::parse(&page_url(&self.scrape, page))?;
+            let resp = client.get_source(&url).await?;
+            (url, resp)
+          }
+        };
+        let html = resp.error_for_status()?.text()?;
+        self.parse_page(split, html, &url)
+      })
+      .await
+  }
+
+  fn parse_page(
+    &self,
+    split: &Split,
+    html: String,
+    url: &Url,
+  ) -> Result<(Feed, Option<Url>)> {
+    let next = self.select_next_page(&html, url)?;
+
+    let (title, description) = self.select_feed_meta(&html)?;
+    let mut feed = Feed::from(&FromScratch {
+      format: self.format.unwrap_or(FeedFormat::Rss),
+      title: title.unwrap_or_else(|| url.to_string()),
+      link: Some(url.to_string()),
+      description,
+    });
+
+    // the whole page as a single post, which then gets split like
+    // the `split` filter does
+    feed.add_post(NormalizedPost {
+      title: feed.title().to_owned(),
+      link: url.to_string(),
+      body: Some(html),
+      ..Default::default()
+    });
+
+    let mut posts = vec![];
+    for post in feed.take_posts() {
+      posts.extend(split.split(post)?);
+    }
+    feed.set_posts(posts);
+
+    Ok((feed, next))
+  }
+
+  fn select_next_page(&self, html: &str, url: &Url) -> Result<Option<Url>> {
+    let Some(NextPage::Selector(selector)) =
+      self.pagination.as_ref().map(Pagination::next_page)
+    else {
+      return Ok(None);
+    };
+
+    let doc = Html::parse_document(html);
+    let next = doc
+      .select(&parse_selector(selector)?)
+      .next()
+      .and_then(|e| e.value().attr("href"))
+      .and_then(|href| url.join(href).ok());
+    Ok(next)
+  }
+
+  fn select_feed_meta(
+    &self,
+    html: &str,
+  ) -> Result<(Option<String>, Option<String>)> {
+    let doc = Html::parse_document(html);
+    let select_text = |selector: &Selector| {
+      doc
+        .select(selector)
+        .next()
+        .map(|e| e.text().collect::<String>().trim().to_owned())
+        .filter(|s| !s.is_empty())
+    };
+
+    let title_selector = self.feed_title_selector.as_deref().unwrap_or("title");
+    let title = select_text(&parse_selector(title_selector)?);
+
+    let description = match &self.feed_description_selector {
+      Some(selector) => select_text(&parse_selector(selector)?),
+      None => doc
+        .select(&parse_selector(r#"meta[name="description"]"#)?)
+        .next()
+        .and_then(|e| e.value().attr("content"))
+        .map(|s| s.trim().to_owned()),
+    };
+
+    Ok((title, description))
+  }
+}
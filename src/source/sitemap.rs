@@ -0,0 +1,347 @@
+use std::io::Read as _;
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use glob_match::glob_match;
+use quick_xml::{NsReader, events::Event, name::ResolveResult};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use url::Url;
+
+use crate::{
+  client::Client,
+  error::{ResponseTooLarge, Result},
+  feed::{Feed, FeedFormat, NormalizedPost},
+  util::parse_date,
+};
+
+use super::feed_from_posts;
+
+const DEFAULT_LIMIT: usize = 20;
+const DEFAULT_MAX_SITEMAPS: usize = 10;
+const NEWS_NAMESPACE: &[u8] = b"http://www.google.com/schemas/sitemap-news/0.9";
+
+#[serde_with::skip_serializing_none]
+#[derive(
+  JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash,
+)]
+pub struct SitemapSource {
+  /// The url of the sitemap or sitemap index. Gzipped sitemaps are
+  /// supported.
+  pub sitemap: String,
+  /// The title of the feed (Default: the host of the sitemap url)
+  #[serde(default)]
+  pub title: Option<String>,
+  /// The format of the feed (Default: rss)
+  #[serde(default)]
+  pub format: Option<FeedFormat>,
+  /// Only include urls matching the glob pattern. Note `*` doesn't
+  /// match "/", use `**` for that (e.g. "https://example.com/blog/**")
+  #[serde(default)]
+  pub include_glob: Option<String>,
+  /// Only include urls matching the regular expression
+  #[serde(default)]
+  pub include_regex: Option<String>,
+  /// Keep the most recent entries (Default: 20)
+  #[serde(default)]
+  pub limit: Option<usize>,
+  /// The maximum number of sitemaps to fetch from a sitemap index,
+  /// the most recently modified ones first (Default: 10)
+  #[serde(default)]
+  pub max_sitemaps: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+struct Entry {
+  loc: String,
+  lastmod: Option<String>,
+  title: Option<String>,
+}
+
+#[derive(Debug)]
+enum Sitemap {
+  UrlSet(Vec<Entry>),
+  Index(Vec<Entry>),
+}
+
+impl SitemapSource {
+  pub fn validate(&self) -> Result<()> {
+    Url::parse(&self.sitemap)?;
+    if let Some(regex) = &self.include_regex {
+      Regex::new(regex)?;
+    }
+    Ok(())
+  }
+
+  pub async fn fetch_feed(&self, client: &Client) -> Result<Feed> {
+    let url = Url::parse(&self.sitemap)?;
+    let mut entries = match fetch_sitemap(client, &url).await? {
+      Sitemap::UrlSet(entries) => entries,
+      Sitemap::Index(mut sitemaps) => {
+        sort_by_lastmod(&mut sitemaps);
+        sitemaps.truncate(self.max_sitemaps.unwrap_or(DEFAULT_MAX_SITEMAPS));
+
+        let mut entries = vec![];
+        for sitemap in sitemaps {
+          let sitemap_url = url.join(&sitemap.loc)?;
+          // a broken sitemap shouldn't hide the entries of the others,
+          // and nested sitemap indexes are not followed
+          match fetch_sitemap(client, &sitemap_url).await {
+            Ok(Sitemap::UrlSet(more)) => entries.extend(more),
+            Ok(Sitemap::Index(_)) => {}
+            Err(e) => warn!("skipped sitemap {sitemap_url}: {e}"),
+          }
+        }
+        entries
+      }
+    };
+
+    let include_regex = self.include_regex.as_deref().map(Regex::new);
+    let include_regex = include_regex.transpose()?;
+    entries.retain(|entry| {
+      entry.lastmod.as_deref().and_then(parse_lastmod).is_some()
+        && self
+          .include_glob
+          .as_deref()
+          .is_none_or(|glob| glob_match(glob, &entry.loc))
+        && include_regex
+          .as_ref()
+          .is_none_or(|re| re.is_match(&entry.loc))
+    });
+    sort_by_lastmod(&mut entries);
+    entries.truncate(self.limit.unwrap_or(DEFAULT_LIMIT));
+
+    let title = self
+      .title
+      .clone()
+      .or_else(|| url.host_str().map(ToOwned::to_owned))
+      .unwrap_or_else(|| self.sitemap.clone());
+    let link = Some(url.origin().ascii_serialization());
+    let posts = entries.into_iter().map(|entry| NormalizedPost {
+      title: entry.title.unwrap_or_else(|| title_from_url(&entry.loc)),
+      date: entry.lastmod.as_deref().and_then(parse_lastmod),
+      link: entry.loc,
+      ..Default::default()
+    });
+
+    Ok(feed_from_posts(self.format, title, link, posts))
+  }
+}
+
+async fn fetch_sitemap(client: &Client, url: &Url) -> Result<Sitemap> {
+  let resp = client.get(url).await?.error_for_status()?;
+  let body = resp.body();
+
+  // gzip magic number
+  if body.starts_with(&[0x1f, 0x8b]) {
+    // the limit applies to the decompressed size too, against gzip
+    // bombs
+    let max_size = client.max_body_size();
+    let mut content = vec![];
+    flate2::read::GzDecoder::new(body)
+      .take(max_size as u64 + 1)
+      .read_to_end(&mut content)?;
+    if content.len() > max_size {
+      return Err(ResponseTooLarge(url.clone(), max_size).into());
+    }
+    return parse_sitemap(&content);
+  }
+
+  parse_sitemap(body)
+}
+
+// most recent first, entries without lastmod last
+fn sort_by_lastmod(entries: &mut [Entry]) {
+  entries.sort_by_cached_key(|entry| {
+    std::cmp::Reverse(entry.lastmod.as_deref().and_then(parse_lastmod))
+  });
+}
+
+// W3C Datetime, which sitemaps use, allows a date without the time
+fn parse_lastmod(s: &str) -> Option<DateTime<FixedOffset>> {
+  parse_date(s).or_else(|| {
+    let date = NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok()?;
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc().fixed_offset())
+  })
+}
+
+// https://example.com/blog/hello-world/ -> "hello world"
+fn title_from_url(url: &str) -> String {
+  let segment = Url::parse(url).ok().and_then(|url| {
+    let mut segments = url.path_segments()?;
+    segments
+      .rfind(|s| !s.is_empty())
+      .map(|s| urlencoding::decode(s).map_or(s.to_owned(), |s| s.into_owned()))
+  });
+
+  match segment {
+    Some(segment) => segment.replace(['-', '_'], " "),
+    None => url.to_owned(),
+  }
+}
+
+// the elements of an entry we read
+enum Field {
+  Loc,
+  Lastmod,
+  Title,
+}
+
+fn parse_sitemap(content: &[u8]) -> Result<Sitemap> {
+  let mut reader = NsReader::from_reader(content);
+  reader.trim_text(true).expand_empty_elements(true);
+
+  let mut is_index = None;
+  let mut entries = vec![];
+  let mut entry: Option<Entry> = None;
+  let mut field: Option<Field> = None;
+  let mut depth = 0usize;
+
+  loop {
+    let (ns, event) = reader.read_resolved_event()?;
+    match event {
+      Event::Start(e) => {
+        depth += 1;
+        let name = e.local_name();
+        let is_news = matches!(
+          ns,
+          ResolveResult::Bound(ns) if ns.as_ref() == NEWS_NAMESPACE
+        );
+        // only the direct children of an entry, so the urls and titles
+        // of image and video extensions are ignored
+        match (depth, name.as_ref()) {
+          (1, b"urlset") => is_index = Some(false),
+          (1, b"sitemapindex") => is_index = Some(true),
+          (2, b"url" | b"sitemap") => entry = Some(Entry::default()),
+          (3, b"loc") => field = Some(Field::Loc),
+          (3, b"lastmod") => field = Some(Field::Lastmod),
+          // <news:news><news:title> in Google News sitemaps
+          (4, b"title") if is_news => field = Some(Field::Title),
+          _ => field = None,
+        }
+      }
+      Event::Text(t) => {
+        if let (Some(entry), Some(field)) = (&mut entry, &field) {
+          let text = t.unescape()?.into_owned();
+          match field {
+            Field::Loc => entry.loc = text,
+            Field::Lastmod => entry.lastmod = Some(text),
+            Field::Title => entry.title = Some(text),
+          }
+        }
+      }
+      Event::End(_) => {
+        field = None;
+        if depth == 2
+          && let Some(entry) = entry.take().filter(|e| !e.loc.is_empty())
+        {
+          entries.push(entry);
+        }
+        depth = depth.saturating_sub(1);
+      }
+      Event::Eof => break,
+      _ => {}
+    }
+  }
+
+  match is_index {
+    Some(true) => Ok(Sitemap::Index(entries)),
+    Some(false) => Ok(Sitemap::UrlSet(entries)),
+    None => anyhow::bail!("not a sitemap: missing <urlset> or <sitemapindex>"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_utils::serve;
+
+  #[test]
+  fn test_parse_image_sitemap() {
+    let content = br#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"
+        xmlns:image="http://www.google.com/schemas/sitemap-image/1.1">
+  <url>
+    <loc>https://example.com/gallery</loc>
+    <image:image>
+      <image:loc>https://example.com/photo.jpg</image:loc>
+      <image:title>A photo</image:title>
+    </image:image>
+    <lastmod>2024-01-05</lastmod>
+  </url>
+</urlset>"#;
+
+    let Sitemap::UrlSet(entries) = parse_sitemap(content).unwrap() else {
+      panic!("expected a urlset");
+    };
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].loc, "https://example.com/gallery");
+    assert_eq!(entries[0].lastmod.as_deref(), Some("2024-01-05"));
+    assert_eq!(entries[0].title, None);
+  }
+
+  #[tokio::test]
+  async fn test_skip_failed_sitemap() {
+    let index = r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc>/missing.xml</loc></sitemap>
+  <sitemap><loc>/pages.xml</loc></sitemap>
+</sitemapindex>"#;
+    let pages = r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://example.com/about</loc><lastmod>2024-01-05</lastmod></url>
+</urlset>"#;
+    let app = axum::Router::new()
+      .route(
+        "/index.xml",
+        axum::routing::get(move || async move { index }),
+      )
+      .route(
+        "/pages.xml",
+        axum::routing::get(move || async move { pages }),
+      );
+    let base = serve(app).await;
+
+    let source = SitemapSource {
+      sitemap: base.join("index.xml").unwrap().to_string(),
+      title: None,
+      format: None,
+      include_glob: None,
+      include_regex: None,
+      limit: None,
+      max_sitemaps: None,
+    };
+    let client = crate::client::ClientConfig::default()
+      .build(std::time::Duration::from_secs(1))
+      .unwrap();
+    let feed = source.fetch_feed(&client).await.unwrap().normalize();
+    assert_eq!(feed.posts.len(), 1);
+    assert_eq!(feed.posts[0].link, "https://example.com/about");
+  }
+
+  #[tokio::test]
+  async fn test_gzip_size_limit() {
+    use std::io::Write as _;
+
+    let mut encoder =
+      flate2::write::GzEncoder::new(vec![], flate2::Compression::best());
+    encoder.write_all(&[b' '; 10_000]).unwrap();
+    let gzipped = encoder.finish().unwrap();
+    let app = axum::Router::new().route(
+      "/sitemap.xml.gz",
+      axum::routing::get(move || async move { gzipped }),
+    );
+    let base = serve(app).await;
+
+    let client = crate::client::ClientConfig {
+      max_body_size: Some(1000),
+      ..Default::default()
+    }
+    .build(std::time::Duration::from_secs(1))
+    .unwrap();
+    let url = base.join("sitemap.xml.gz").unwrap();
+    let Err(err) = fetch_sitemap(&client, &url).await else {
+      panic!("decompressed sitemap should be too large");
+    };
+    assert!(err.downcast_ref::<ResponseTooLarge>().is_some());
+  }
+}
@@ -4,6 +4,7 @@ use http::Request;
 use mime::Mime;
 use serde::Serialize;
 use tower::Service;
+use url::Url;
 
 use crate::{
   client::{Client, ClientConfig},
@@ -31,11 +32,12 @@ where
   assert_eq!(actual_serialized, expected_serialized);
 }
 
-const VALID_CONTENT_TYPES: [&str; 4] = [
+const VALID_CONTENT_TYPES: [&str; 5] = [
   "application/xml",
   "text/xml",
   "application/rss+xml",
   "application/atom+xml",
+  "application/feed+json",
 ];
 
 pub async fn fetch_endpoint(config: &str, query: &str) -> Feed {
@@ -79,9 +81,44 @@ pub async fn fetch_endpoint(config: &str, query: &str) -> Feed {
     .await
     .expect("failed to read body");
 
+  if mime.essence_str() == "application/feed+json" {
+    return Feed::from_json_content(&body).expect("failed to parse feed");
+  }
+
   Feed::from_xml_content(&body).expect("failed to parse feed")
 }
 
+/// Run a single filter on the posts of `fixtures/duplicates.xml` and
+/// return their titles.
+pub async fn fetch_duplicates_titles(name: &str, config: &str) -> Vec<String> {
+  let config = format!(
+    r"
+    !endpoint
+    path: /feed.xml
+    source: fixture:///duplicates.xml
+    filters:
+      - {name}: {config}
+    "
+  );
+
+  let mut feed = fetch_endpoint(&config, "").await;
+  feed
+    .take_posts()
+    .iter()
+    .map(|post| post.title().unwrap().to_owned())
+    .collect()
+}
+
+/// Serve the router on a random local port and return its base url.
+pub async fn serve(app: axum::Router) -> Url {
+  let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+    .await
+    .expect("failed to bind");
+  let addr = listener.local_addr().expect("failed to get local address");
+  tokio::spawn(async move { axum::serve(listener, app).await });
+  Url::parse(&format!("http://{addr}/")).expect("invalid url")
+}
+
 fn dummy_client() -> Client {
   ClientConfig::default()
     .build(Duration::from_secs(10))
@@ -24,6 +24,13 @@ pub fn is_env_set(name: &str) -> bool {
   matches!(val.as_str(), "1" | "t" | "true" | "y" | "yes")
 }
 
+/// Whether the domain matches any of the glob patterns.
+pub fn domain_matches(patterns: &[String], domain: &str) -> bool {
+  patterns
+    .iter()
+    .any(|pat| glob_match::glob_match(pat, domain))
+}
+
 mod path_prefix {
   use std::sync::LazyLock;
 
@@ -69,6 +76,37 @@ mod app_base {
 
 pub use self::app_base::app_base_from_env;
 
+mod config_dir {
+  use std::{
+    path::{Path, PathBuf},
+    sync::RwLock,
+  };
+
+  static CONFIG_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+  /// Remember the directory of the loaded config file, so relative
+  /// paths in the config can be resolved against it.
+  pub fn set_config_dir(config_path: &Path) {
+    let dir = config_path.parent().map(Path::to_path_buf);
+    if let Ok(mut config_dir) = CONFIG_DIR.write() {
+      *config_dir = dir;
+    }
+  }
+
+  pub fn resolve_config_path(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+      return path.to_path_buf();
+    }
+
+    match CONFIG_DIR.read().ok().and_then(|dir| dir.clone()) {
+      Some(dir) => dir.join(path),
+      None => path.to_path_buf(),
+    }
+  }
+}
+
+pub use self::config_dir::{resolve_config_path, set_config_dir};
+
 mod single_or_vec {
   use schemars::JsonSchema;
   use serde::{Deserialize, Serialize};
@@ -177,9 +215,9 @@ mod cache {
         return None;
       };
 
+      // expired entries are kept around for revalidation
       if entry.created.elapsed() > self.timeout {
         self.misses.fetch_add(1, Ordering::Relaxed);
-        map.pop(key);
         return None;
       }
 
@@ -187,10 +225,28 @@ mod cache {
       Some(entry.value.clone())
     }
 
+    /// Get the entry even if it has expired. Doesn't count as a hit or
+    /// a miss.
+    pub fn get_stale(&self, key: &K) -> Option<V> {
+      let map = self.map.read().ok()?;
+      map.peek(key).map(|entry| entry.value.clone())
+    }
+
     pub fn insert(&self, key: K, value: V) -> Option<()> {
+      self.insert_with_age(key, value, Duration::ZERO)
+    }
+
+    /// Insert an entry that was created `age` ago elsewhere.
+    pub fn insert_with_age(
+      &self,
+      key: K,
+      value: V,
+      age: Duration,
+    ) -> Option<()> {
+      let now = Instant::now();
       let timed = Timed {
         value,
-        created: Instant::now(),
+        created: now.checked_sub(age).unwrap_or(now),
       };
       self.map.write().ok()?.push(key, timed);
       Some(())